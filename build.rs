@@ -1,43 +1,70 @@
 use std::{
-    env,
+    env, fs,
     path::{Path, PathBuf},
     process::Command,
 };
 
-fn ensure_submodule(path: &Path) {
-    // Heuristic: if the directory exists and contains vkFFT.h, assume initialized.
-    if path.join("vkFFT").join("vkFFT.h").is_file() {
+fn ensure_submodule(manifest_dir: &Path, submodule: &str, marker: &str) {
+    // Heuristic: if the marker file exists, assume the submodule is initialized.
+    if manifest_dir.join(submodule).join(marker).is_file() {
         return;
     }
 
-    // Run: git submodule update --init --recursive vendor/VkFFT
+    // Hermetic builds forbid git (and network access) in build scripts.
+    if env::var_os("VKFFT_NO_GIT").is_some() {
+        panic!(
+            "{submodule} is not checked out ({marker} is missing) and VKFFT_NO_GIT is set. \
+             Run `git submodule update --init --recursive {submodule}` before building, \
+             or point the build at an existing copy (see VKFFT_INCLUDE_DIR and \
+             GLSLANG_INCLUDE_DIR)."
+        );
+    }
+
+    // Run: git submodule update --init --recursive <submodule>
     let status = Command::new("git")
-        .args([
-            "submodule",
-            "update",
-            "--init",
-            "--recursive",
-            "vendor/VkFFT",
-        ])
-        .current_dir(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .args(["submodule", "update", "--init", "--recursive", submodule])
+        .current_dir(manifest_dir)
         .status()
         .expect("failed to execute git to init/update submodule");
 
     if !status.success() {
-        panic!("git submodule update --init --recursive vendor/VkFFT failed");
+        panic!("git submodule update --init --recursive {submodule} failed");
     }
 }
 
+/// Directory named by the environment variable `name`, if set.
+fn env_dir(name: &str) -> Option<PathBuf> {
+    println!("cargo:rerun-if-env-changed={name}");
+    env::var_os(name).map(PathBuf::from)
+}
+
+/// Directory containing vkFFT.h: `VKFFT_INCLUDE_DIR`, else the vendor/VkFFT submodule.
+fn vkfft_include_dir(manifest_dir: &Path) -> PathBuf {
+    if let Some(dir) = env_dir("VKFFT_INCLUDE_DIR") {
+        assert!(
+            dir.join("vkFFT.h").is_file(),
+            "VKFFT_INCLUDE_DIR={} does not contain vkFFT.h",
+            dir.display()
+        );
+        return dir;
+    }
+    ensure_submodule(manifest_dir, "vendor/VkFFT", "vkFFT/vkFFT.h");
+    manifest_dir.join("vendor").join("VkFFT").join("vkFFT")
+}
+
 fn main() {
     println!("cargo:rerun-if-changed=src/shim.cpp");
 
     let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
-    let vkfft_dir = manifest_dir.join("vendor").join("VkFFT");
-    let vkfft_include = vkfft_dir.join("vkFFT"); // contains vkFFT.h
-
-    ensure_submodule(&vkfft_dir);
+    println!("cargo:rerun-if-env-changed=VKFFT_NO_GIT");
+    let vkfft_include = vkfft_include_dir(&manifest_dir); // contains vkFFT.h
+    let vkfft_dir = vkfft_include
+        .parent()
+        .expect("VKFFT_INCLUDE_DIR has no parent directory")
+        .to_path_buf();
 
     println!("cargo:rerun-if-changed=wrapper.h");
+    println!("cargo:rerun-if-changed=vkfft_trace.h");
     println!(
         "cargo:rerun-if-changed={}",
         vkfft_include.join("vkFFT.h").display()
@@ -47,35 +74,45 @@ fn main() {
     // Generate bindings.
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
 
-    let glslang_include = find_glslang_c_interface_dir().expect(
-        "Could not find glslang_c_interface.h. Install glslang dev headers or set VULKAN_SDK.",
-    );
+    // precompiled-only still compiles the shim against glslang's headers,
+    // which VkFFT includes, but never looks for or links the library.
+    #[cfg(all(feature = "vendored-glslang", feature = "precompiled-only"))]
+    compile_error!("`precompiled-only` builds without glslang; disable `vendored-glslang`");
+    #[cfg(feature = "precompiled-only")]
+    let glslang_include = &glslang_headers(&manifest_dir);
+    #[cfg(all(feature = "vendored-glslang", not(feature = "precompiled-only")))]
+    let glslang = vendored_glslang(&manifest_dir);
+    #[cfg(not(any(feature = "vendored-glslang", feature = "precompiled-only")))]
+    let glslang = system_glslang();
+    #[cfg(not(feature = "precompiled-only"))]
+    let glslang_include = &glslang.include;
 
     println!(
         "cargo:rerun-if-changed={}",
-        vkfft_dir
+        vkfft_include
             .join("vkFFT/vkFFT_AppManagement/vkFFT_InitializeApp.h")
             .display()
     );
     println!(
         "cargo:rerun-if-changed={}",
-        vkfft_dir
+        vkfft_include
             .join("vkFFT/vkFFT_AppManagement/vkFFT_RunApp.h")
             .display()
     );
     println!(
         "cargo:rerun-if-changed={}",
-        vkfft_dir
+        vkfft_include
             .join("vkFFT/vkFFT_AppManagement/vkFFT_DeleteApp.h")
             .display()
     );
 
-    cc::Build::new()
-        .cpp(true)
+    let mut shim = cc::Build::new();
+    shim.cpp(true)
         .file(manifest_dir.join("src/shim.cpp"))
+        .include(&manifest_dir) // for vkfft_trace.h
         .include(&vkfft_include) // for vkFFT.h
         .include(&vkfft_dir) // for vkFFT/...
-        .include(&glslang_include) // for glslang_c_interface.h
+        .include(glslang_include) // for glslang_c_interface.h
         .flag_if_supported("-std=c++17")
         .flag_if_supported("-w") // GCC/Clang: suppress all warnings
         .flag_if_supported("-Wno-everything") // Clang
@@ -83,12 +120,114 @@ fn main() {
         .flag_if_supported("-Wno-unused-variable")
         .flag_if_supported("-Wno-sign-compare")
         .flag_if_supported("-Wno-old-style-cast")
-        .flag_if_supported("-Wno-shadow")
-        .compile("vkfft_shim");
+        .flag_if_supported("-Wno-shadow");
+    if cfg!(feature = "precompiled-only") {
+        shim.define("VKFFT_PRECOMPILED_ONLY", None);
+    } else if cfg!(feature = "dlopen-glslang") {
+        shim.define("VKFFT_DLOPEN_GLSLANG", None);
+    }
+    shim.compile("vkfft_shim");
 
     println!("cargo:rustc-link-lib=static=vkfft_shim");
     println!("cargo:rustc-link-search=native={}", out_dir.display());
+    // precompiled-only has no shader compiler: nothing to link.
+    #[cfg(not(feature = "precompiled-only"))]
+    if cfg!(feature = "dlopen-glslang") {
+        // The shim opens glslang itself when the first plan is initialized.
+        if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux") {
+            println!("cargo:rustc-link-lib=dl");
+        }
+    } else {
+        // After the shim: static glslang libraries must follow the code using them.
+        glslang.emit_link_directives();
+    }
+
+    // Bindings: checked in under bindings/<backend>/<version>-<target>.rs.
+    // bindgen (and libclang) runs only to refresh them under
+    // `regenerate-bindings`, or for targets without a checked-in file when
+    // the `bindgen` feature is enabled.
+    let version = vkfft_version(&vkfft_include);
+    if let Some(commit) = git_commit(&vkfft_dir) {
+        println!("cargo:rustc-env=VKFFT_COMMIT={commit}");
+    }
+    let header_hash = hash_headers(&manifest_dir, &vkfft_include);
+    let pregenerated = manifest_dir.join("bindings").join(BACKEND).join(format!(
+        "{}-{}.rs",
+        version_string(version),
+        target_triple_name()
+    ));
+    println!("cargo:rerun-if-changed={}", pregenerated.display());
+
+    #[cfg(feature = "regenerate-bindings")]
+    let bindings = {
+        let bindings = generate_bindings(
+            &manifest_dir,
+            &vkfft_include,
+            &vkfft_dir,
+            glslang_include,
+            version,
+            header_hash,
+        );
+        fs::create_dir_all(pregenerated.parent().unwrap())
+            .expect("failed to create the bindings directory");
+        fs::write(&pregenerated, &bindings).expect("failed to write the pre-generated bindings");
+        bindings
+    };
+    #[cfg(not(feature = "regenerate-bindings"))]
+    let bindings = match pregenerated_bindings(&pregenerated, header_hash) {
+        Some(bindings) => bindings,
+        #[cfg(feature = "bindgen")]
+        None => generate_bindings(
+            &manifest_dir,
+            &vkfft_include,
+            &vkfft_dir,
+            glslang_include,
+            version,
+            header_hash,
+        ),
+        #[cfg(not(feature = "bindgen"))]
+        None => panic!(
+            "No pre-generated {BACKEND} bindings for VkFFT {} on this target (expected {}). \
+             Enable the `bindgen` feature (needs libclang) to generate them during the build, \
+             or `regenerate-bindings` to write that file.",
+            version_string(version),
+            pregenerated.display()
+        ),
+    };
+
+    fs::write(out_dir.join("bindings.rs"), bindings).expect("failed to write bindings.rs");
+}
 
+/// VkFFT backend the shim and bindings are built for (`VKFFT_BACKEND=0`).
+const BACKEND: &str = "vulkan";
+
+/// Marker line recording the header hash in pre-generated bindings.
+const HEADER_HASH_PREFIX: &str = "// VkFFT header hash: ";
+
+/// The parts of the target that change bindgen's output: the OS and ABI
+/// (enum representation, `long` size) and the pointer width, e.g.
+/// `linux-gnu-64` or `windows-msvc-64`.
+fn target_triple_name() -> String {
+    let cfg = |name: &str| env::var(format!("CARGO_CFG_TARGET_{name}")).unwrap_or_default();
+    let (os, target_env, width) = (cfg("OS"), cfg("ENV"), cfg("POINTER_WIDTH"));
+    if target_env.is_empty() {
+        format!("{os}-{width}")
+    } else {
+        format!("{os}-{target_env}-{width}")
+    }
+}
+
+/// Run bindgen and prefix the output with the version and header hash it was
+/// generated from.
+#[cfg(feature = "bindgen")]
+fn generate_bindings(
+    manifest_dir: &Path,
+    vkfft_include: &Path,
+    vkfft_dir: &Path,
+    glslang_include: &Path,
+    version: u32,
+    header_hash: u64,
+) -> String {
     let bindings = bindgen::Builder::default()
         .header(manifest_dir.join("wrapper.h").to_string_lossy())
         .clang_arg(format!("-I{}", vkfft_include.display()))
@@ -119,35 +258,416 @@ fn main() {
         .generate()
         .expect("bindgen: failed to generate VkFFT bindings");
 
-    bindings
-        .write_to_file(out_dir.join("bindings.rs"))
-        .expect("bindgen: failed to write bindings.rs");
+    format!(
+        "// Generated by bindgen from the VkFFT {} headers ({BACKEND} backend, {}); do not edit.\n\
+     // Rebuild with `--features regenerate-bindings` after updating VkFFT.\n\
+     {HEADER_HASH_PREFIX}{header_hash:016x}\n\n{}",
+        version_string(version),
+        target_triple_name(),
+        bindings.to_string(),
+    )
 }
 
-fn find_glslang_c_interface_dir() -> Option<PathBuf> {
-    // VkFFT includes: "glslang_c_interface.h" (no path),
-    // so we must add an include dir that directly contains that file.
-    let mut candidates: Vec<PathBuf> = Vec::new();
-
-    if let Ok(sdk) = env::var("VULKAN_SDK") {
-        // Vulkan SDK layout:
-        //   $VULKAN_SDK/Include/glslang/Include/glslang_c_interface.h
-        candidates.push(
-            PathBuf::from(&sdk)
-                .join("Include")
-                .join("glslang")
-                .join("Include"),
+/// Checked-in bindings at `path`, if there are any, refusing ones generated
+/// from other headers.
+#[cfg(not(feature = "regenerate-bindings"))]
+fn pregenerated_bindings(path: &Path, header_hash: u64) -> Option<String> {
+    let bindings = fs::read_to_string(path).ok()?;
+    let recorded = bindings
+        .lines()
+        .find_map(|line| line.strip_prefix(HEADER_HASH_PREFIX))
+        .and_then(|hash| u64::from_str_radix(hash.trim(), 16).ok());
+    if recorded != Some(header_hash) {
+        panic!(
+            "{} was generated from different VkFFT headers than the ones being built \
+             (header hash {:016x}, bindings record {}). Rebuild with \
+             `--features regenerate-bindings` to update them.",
+            path.display(),
+            header_hash,
+            recorded.map_or_else(|| "none".to_string(), |hash| format!("{hash:016x}"))
         );
     }
+    Some(bindings)
+}
 
-    // Common system layouts (Debian/Ubuntu/etc):
-    candidates.push(PathBuf::from("/usr/include/glslang/Include"));
-    candidates.push(PathBuf::from("/usr/local/include/glslang/Include"));
+/// `VkFFTGetVersion()`'s return value, e.g. 10304 for 1.3.4.
+fn vkfft_version(vkfft_include: &Path) -> u32 {
+    header_files(vkfft_include)
+        .iter()
+        .find_map(|path| {
+            let source = fs::read_to_string(path).ok()?;
+            let body = &source[source.find("VkFFTGetVersion(")?..];
+            let value = body[body.find("return")? + "return".len()..].trim_start();
+            let digits = value.find(|c: char| !c.is_ascii_digit())?;
+            value[..digits].parse().ok()
+        })
+        .expect("could not find VkFFTGetVersion() in the VkFFT headers")
+}
+
+/// Commit checked out in the git repository at `repo`, read from its metadata
+/// so that no git command runs.
+fn git_commit(repo: &Path) -> Option<String> {
+    let dot_git = repo.join(".git");
+    // A submodule's .git is a file pointing at its git directory.
+    let git_dir = match fs::read_to_string(&dot_git) {
+        Ok(link) => repo.join(link.strip_prefix("gitdir:")?.trim()),
+        Err(_) => dot_git,
+    };
+    let head_path = git_dir.join("HEAD");
+    println!("cargo:rerun-if-changed={}", head_path.display());
+    let head = fs::read_to_string(head_path).ok()?;
+    let Some(reference) = head.trim().strip_prefix("ref:") else {
+        // Detached HEAD, as submodules usually are.
+        return Some(head.trim().to_string());
+    };
+    let reference = reference.trim();
+    if let Ok(commit) = fs::read_to_string(git_dir.join(reference)) {
+        return Some(commit.trim().to_string());
+    }
+    fs::read_to_string(git_dir.join("packed-refs"))
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let (commit, name) = line.split_once(' ')?;
+            (name == reference).then(|| commit.to_string())
+        })
+}
+
+/// VkFFT encodes its version as X.XX.XX.
+fn version_string(version: u32) -> String {
+    format!(
+        "{}.{}.{}",
+        version / 10000,
+        version / 100 % 100,
+        version % 100
+    )
+}
+
+/// FNV-1a over the VkFFT headers and our own headers, with their paths, in a
+/// fixed order.
+fn hash_headers(manifest_dir: &Path, vkfft_include: &Path) -> u64 {
+    let own = ["wrapper.h", "vkfft_trace.h"].map(|name| manifest_dir.join(name));
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    for path in header_files(vkfft_include) {
+        // Hash the same bytes on every host.
+        let relative = path.strip_prefix(vkfft_include).unwrap();
+        feed(relative.to_string_lossy().replace('\\', "/").as_bytes());
+        feed(&fs::read(&path).expect("failed to read a VkFFT header"));
+    }
+    for path in own {
+        feed(&fs::read(&path).expect("failed to read wrapper headers"));
+    }
+    hash
+}
+
+/// All `.h` files under `dir`, sorted.
+fn header_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "h") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// glslang's libraries in link order. Which of them exist depends on the
+/// glslang version (SPIRV and OGLCompiler were folded into glslang in 14.x).
+#[cfg(not(feature = "precompiled-only"))]
+const GLSLANG_LIBS: [&str; 7] = [
+    "glslang",
+    "SPIRV",
+    "MachineIndependent",
+    "OSDependent",
+    "GenericCodeGen",
+    "OGLCompiler",
+    "glslang-default-resource-limits",
+];
+
+#[cfg(not(feature = "precompiled-only"))]
+struct Glslang {
+    /// Directory containing glslang_c_interface.h.
+    include: PathBuf,
+    /// Directory with the glslang libraries; `None` leaves the search to the linker.
+    lib_dir: Option<PathBuf>,
+    /// Libraries reported by pkg-config, linked as given instead of probing `lib_dir`.
+    pkg_config: Option<pkg_config::Library>,
+    /// Link the static libraries even if shared ones are present.
+    static_only: bool,
+}
+
+#[cfg(not(feature = "precompiled-only"))]
+impl Glslang {
+    fn emit_link_directives(&self) {
+        if let Some(library) = &self.pkg_config {
+            for dir in &library.link_paths {
+                println!("cargo:rustc-link-search=native={}", dir.display());
+            }
+            for name in &library.libs {
+                println!("cargo:rustc-link-lib={name}");
+            }
+            return;
+        }
+        let Some(lib_dir) = &self.lib_dir else {
+            println!("cargo:rustc-link-lib=glslang");
+            return;
+        };
+        println!("cargo:rustc-link-search=native={}", lib_dir.display());
 
-    for dir in candidates {
-        if dir.join("glslang_c_interface.h").is_file() {
-            return Some(dir);
+        let shared = !self.static_only && shared_library_exists(lib_dir, "glslang");
+        for name in GLSLANG_LIBS {
+            if shared {
+                if shared_library_exists(lib_dir, name) {
+                    println!("cargo:rustc-link-lib=dylib={name}");
+                }
+            } else if static_library_exists(lib_dir, name) {
+                println!("cargo:rustc-link-lib=static={name}");
+            }
         }
+        // OSDependent uses pthreads on Unix.
+        if !shared && env::var("CARGO_CFG_TARGET_FAMILY").as_deref() == Ok("unix") {
+            println!("cargo:rustc-link-lib=pthread");
+        }
+    }
+}
+
+#[cfg(not(any(feature = "vendored-glslang", feature = "precompiled-only")))]
+fn has_glslang_library(dir: &Path) -> bool {
+    static_library_exists(dir, "glslang") || shared_library_exists(dir, "glslang")
+}
+
+#[cfg(not(feature = "precompiled-only"))]
+fn static_library_exists(dir: &Path, name: &str) -> bool {
+    dir.join(format!("lib{name}.a")).is_file() || dir.join(format!("{name}.lib")).is_file()
+}
+
+#[cfg(not(feature = "precompiled-only"))]
+fn shared_library_exists(dir: &Path, name: &str) -> bool {
+    dir.join(format!("lib{name}.so")).is_file() || dir.join(format!("lib{name}.dylib")).is_file()
+}
+
+/// Build glslang from vendor/glslang and link it statically.
+#[cfg(feature = "vendored-glslang")]
+fn vendored_glslang(manifest_dir: &Path) -> Glslang {
+    ensure_submodule(
+        manifest_dir,
+        "vendor/glslang",
+        "glslang/Include/glslang_c_interface.h",
+    );
+
+    // Only the compiler library is needed: no SPIRV-Tools optimizer, HLSL
+    // front end, command-line tools or tests.
+    let install = cmake::Config::new(manifest_dir.join("vendor").join("glslang"))
+        .profile("Release")
+        .define("BUILD_SHARED_LIBS", "OFF")
+        .define("CMAKE_POSITION_INDEPENDENT_CODE", "ON")
+        .define("ENABLE_OPT", "OFF")
+        .define("ENABLE_HLSL", "OFF")
+        .define("ENABLE_GLSLANG_BINARIES", "OFF")
+        .define("ENABLE_SPVREMAPPER", "OFF")
+        .define("ENABLE_CTEST", "OFF")
+        .define("GLSLANG_TESTS", "OFF")
+        .define("GLSLANG_ENABLE_INSTALL", "ON")
+        .define("BUILD_TESTING", "OFF")
+        .build();
+
+    let lib_dir = ["lib", "lib64"]
+        .map(|dir| install.join(dir))
+        .into_iter()
+        .find(|dir| static_library_exists(dir, "glslang"))
+        .expect("vendored glslang build did not install libglslang");
+    Glslang {
+        include: install.join("include").join("glslang").join("Include"),
+        lib_dir: Some(lib_dir),
+        pkg_config: None,
+        static_only: true,
+    }
+}
+
+/// Headers only, for `precompiled-only`: VkFFT includes
+/// glslang_c_interface.h, but no library is searched for or linked.
+///
+/// The directory is `GLSLANG_INCLUDE_DIR`, the Vulkan SDK's, one of the usual
+/// system locations, or else the unbuilt vendor/glslang submodule.
+#[cfg(feature = "precompiled-only")]
+fn glslang_headers(manifest_dir: &Path) -> PathBuf {
+    if let Some(dir) = env_dir("GLSLANG_INCLUDE_DIR") {
+        assert!(
+            dir.join("glslang_c_interface.h").is_file(),
+            "GLSLANG_INCLUDE_DIR={} does not contain glslang_c_interface.h",
+            dir.display()
+        );
+        return dir;
+    }
+    let sdk = env::var_os("VULKAN_SDK").map(PathBuf::from);
+    if let Some(dir) = sdk
+        .as_deref()
+        .and_then(sdk_glslang_include_dir)
+        .or_else(find_glslang_c_interface_dir)
+    {
+        return dir;
+    }
+    ensure_submodule(
+        manifest_dir,
+        "vendor/glslang",
+        "glslang/Include/glslang_c_interface.h",
+    );
+    manifest_dir
+        .join("vendor")
+        .join("glslang")
+        .join("glslang")
+        .join("Include")
+}
+
+/// Use the glslang installed on the system or in the Vulkan SDK.
+///
+/// Each of the header and library directories is taken from, in order:
+/// `GLSLANG_INCLUDE_DIR` / `GLSLANG_LIB_DIR`, `VULKAN_SDK`, `pkg-config glslang`
+/// and the usual system locations.
+#[cfg(not(any(feature = "vendored-glslang", feature = "precompiled-only")))]
+fn system_glslang() -> Glslang {
+    let include_override = env_dir("GLSLANG_INCLUDE_DIR");
+    let lib_override = env_dir("GLSLANG_LIB_DIR");
+    let sdk = env::var_os("VULKAN_SDK").map(PathBuf::from);
+
+    if let Some(dir) = &include_override {
+        assert!(
+            dir.join("glslang_c_interface.h").is_file(),
+            "GLSLANG_INCLUDE_DIR={} does not contain glslang_c_interface.h",
+            dir.display()
+        );
+    }
+    if let Some(dir) = &lib_override {
+        assert!(
+            has_glslang_library(dir),
+            "GLSLANG_LIB_DIR={} does not contain a glslang library",
+            dir.display()
+        );
     }
-    None
+
+    let sdk_include = sdk.as_deref().and_then(sdk_glslang_include_dir);
+    let sdk_lib = sdk.as_deref().and_then(sdk_glslang_lib_dir);
+    let need_include = include_override.is_none() && sdk_include.is_none();
+    let need_lib = lib_override.is_none() && sdk_lib.is_none();
+    let pkg_config = if need_include || need_lib {
+        probe_pkg_config()
+    } else {
+        None
+    };
+
+    let include = include_override
+        .or(sdk_include)
+        .or_else(|| pkg_config.as_ref().and_then(pkg_config_include_dir))
+        .or_else(find_glslang_c_interface_dir)
+        .expect(
+            "Could not find glslang_c_interface.h. Install glslang dev headers, set VULKAN_SDK \
+             or GLSLANG_INCLUDE_DIR, or enable the `vendored-glslang` feature.",
+        );
+
+    if let Some(lib_dir) = lib_override.or(sdk_lib) {
+        return Glslang {
+            include,
+            lib_dir: Some(lib_dir),
+            pkg_config: None,
+            static_only: false,
+        };
+    }
+    match pkg_config.filter(|library| !library.libs.is_empty()) {
+        Some(library) => Glslang {
+            include,
+            lib_dir: None,
+            pkg_config: Some(library),
+            static_only: false,
+        },
+        None => Glslang {
+            include,
+            lib_dir: find_glslang_lib_dir(),
+            pkg_config: None,
+            static_only: false,
+        },
+    }
+}
+
+#[cfg(not(any(feature = "vendored-glslang", feature = "precompiled-only")))]
+fn probe_pkg_config() -> Option<pkg_config::Library> {
+    // Link directives are emitted by `Glslang` so that they follow the shim.
+    pkg_config::Config::new()
+        .cargo_metadata(false)
+        .env_metadata(true)
+        .probe("glslang")
+        .ok()
+}
+
+/// pkg-config usually reports the include root; VkFFT includes the header without a path.
+#[cfg(not(any(feature = "vendored-glslang", feature = "precompiled-only")))]
+fn pkg_config_include_dir(library: &pkg_config::Library) -> Option<PathBuf> {
+    library
+        .include_paths
+        .iter()
+        .flat_map(|dir| [dir.clone(), dir.join("glslang").join("Include")])
+        .find(|dir| dir.join("glslang_c_interface.h").is_file())
+}
+
+/// Vulkan SDK layout: `$VULKAN_SDK/Include/glslang/Include/glslang_c_interface.h`.
+#[cfg(not(feature = "vendored-glslang"))]
+fn sdk_glslang_include_dir(sdk: &Path) -> Option<PathBuf> {
+    let dir = sdk.join("Include").join("glslang").join("Include");
+    dir.join("glslang_c_interface.h").is_file().then_some(dir)
+}
+
+/// Linux/macOS SDKs use lib, the Windows SDK uses Lib.
+#[cfg(not(any(feature = "vendored-glslang", feature = "precompiled-only")))]
+fn sdk_glslang_lib_dir(sdk: &Path) -> Option<PathBuf> {
+    ["lib", "Lib"]
+        .map(|dir| sdk.join(dir))
+        .into_iter()
+        .find(|dir| has_glslang_library(dir))
+}
+
+#[cfg(not(any(feature = "vendored-glslang", feature = "precompiled-only")))]
+fn find_glslang_lib_dir() -> Option<PathBuf> {
+    // These directories hold libraries for the host. Cross builds must name
+    // the target's glslang through GLSLANG_LIB_DIR, VULKAN_SDK or pkg-config.
+    if env::var("TARGET") != env::var("HOST") {
+        return None;
+    }
+    let mut candidates = vec![PathBuf::from("/usr/local/lib")];
+    if let Ok(arch) = env::var("CARGO_CFG_TARGET_ARCH") {
+        // Debian/Ubuntu multiarch layout.
+        candidates.push(PathBuf::from(format!("/usr/lib/{arch}-linux-gnu")));
+    }
+    candidates.push(PathBuf::from("/usr/lib64"));
+    candidates.push(PathBuf::from("/usr/lib"));
+
+    candidates.into_iter().find(|dir| has_glslang_library(dir))
+}
+
+#[cfg(not(feature = "vendored-glslang"))]
+fn find_glslang_c_interface_dir() -> Option<PathBuf> {
+    // VkFFT includes: "glslang_c_interface.h" (no path),
+    // so we must add an include dir that directly contains that file.
+    // Common system layouts (Debian/Ubuntu/etc):
+    let candidates = [
+        PathBuf::from("/usr/include/glslang/Include"),
+        PathBuf::from("/usr/local/include/glslang/Include"),
+    ];
+
+    candidates
+        .into_iter()
+        .find(|dir| dir.join("glslang_c_interface.h").is_file())
 }
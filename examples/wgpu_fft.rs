@@ -0,0 +1,34 @@
+use vkfft_bindings::wgpu_interop::WgpuFft;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Run VkFFT on a buffer that a wgpu renderer can bind directly.
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::VULKAN,
+        ..Default::default()
+    });
+    let adapter = pollster::block_on(instance.request_adapter(&Default::default()))
+        .expect("No Vulkan adapter found");
+    let (device, queue) = pollster::block_on(adapter.request_device(&Default::default(), None))?;
+
+    let fft_len = 1024_u64;
+    let buffer_size = 2 * std::mem::size_of::<f32>() as u64 * fft_len;
+
+    let mut fft = unsafe { WgpuFft::new(&device)? };
+    let buffer = unsafe { fft.create_buffer(&device, buffer_size, wgpu::BufferUsages::empty())? };
+
+    fft.plan_mut().configure_dimensions(&[fft_len]);
+    fft.configure_buffers(&[&buffer], None)?;
+    fft.initialize()?;
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("vkfft"),
+    });
+    unsafe {
+        fft.record(&mut encoder, -1)?;
+    }
+    queue.submit(Some(encoder.finish()));
+    device.poll(wgpu::Maintain::Wait);
+
+    println!("Ran a {fft_len}-point FFT on a wgpu-owned buffer.");
+    Ok(())
+}
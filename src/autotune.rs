@@ -0,0 +1,575 @@
+//! Measure [`TuningOptions`] on a device and remember the fastest.
+//!
+//! [`AutoTuner`] builds the plan once with VkFFT's defaults and then walks
+//! the knobs of a [`TuningSpace`] one at a time, keeping each value that
+//! makes the forward transform faster (coordinate descent). Every candidate
+//! is a full `initialize`, so tuning costs one shader compilation per
+//! candidate; candidates the device rejects are skipped.
+//!
+//! Results are stored in a [`TuningCache`] keyed by device, driver and plan
+//! shape. The cache has a line-based text form (`Display`/`FromStr`) so it
+//! can be saved next to an application's other settings; with the `std`
+//! feature, [`TuningCache::load`] and [`TuningCache::save`] keep it in a file.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{io, path::Path, string::ToString};
+
+use ash::vk;
+
+use crate::context::DeviceContext;
+use crate::trace::{precision_label, PRECISION_LABELS};
+use crate::tuning::TuningOptions;
+use crate::vkfft::{VkFft, VkfftError};
+
+/// Values tried for each knob. Empty lists skip the knob.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TuningSpace {
+    pub coalesced_memory: Vec<u64>,
+    pub aim_threads: Vec<u64>,
+    pub register_boost: Vec<u64>,
+    pub bandwidth_boost: Vec<u64>,
+    pub lut: Vec<bool>,
+}
+
+impl Default for TuningSpace {
+    fn default() -> Self {
+        Self {
+            coalesced_memory: vec![32, 64, 128],
+            aim_threads: vec![64, 128, 256],
+            register_boost: vec![1, 2, 4],
+            bandwidth_boost: vec![0, 2, 4],
+            lut: vec![false, true],
+        }
+    }
+}
+
+impl TuningSpace {
+    const KNOBS: usize = 5;
+
+    /// `base` with knob `knob` set to each of its values.
+    fn variants(&self, knob: usize, base: &TuningOptions) -> Vec<TuningOptions> {
+        let base = *base;
+        match knob {
+            0 => self
+                .coalesced_memory
+                .iter()
+                .map(|&v| TuningOptions {
+                    coalesced_memory: Some(v),
+                    ..base
+                })
+                .collect(),
+            1 => self
+                .aim_threads
+                .iter()
+                .map(|&v| TuningOptions {
+                    aim_threads: Some(v),
+                    ..base
+                })
+                .collect(),
+            2 => self
+                .register_boost
+                .iter()
+                .map(|&v| TuningOptions {
+                    register_boost: Some(v),
+                    ..base
+                })
+                .collect(),
+            3 => self
+                .bandwidth_boost
+                .iter()
+                .map(|&v| TuningOptions {
+                    bandwidth_boost: Some(v),
+                    ..base
+                })
+                .collect(),
+            _ => self
+                .lut
+                .iter()
+                .map(|&v| TuningOptions {
+                    lut: Some(v),
+                    ..base
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Identifies a tuned plan: the device and driver it ran on and the shape of
+/// the transform.
+///
+/// Strides, omitted axes and out-of-place buffers are not part of the key.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TuningKey {
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub driver_version: u32,
+    pub dims: u64,
+    pub size: [u64; 4],
+    pub batches: u64,
+    pub r2c: bool,
+    /// DCT type, 0 for complex and R2C transforms.
+    pub dct: u64,
+    /// `"single"`, `"double"`, `"half"`, `"half-storage"` or
+    /// `"single-storage-double-compute"`.
+    pub precision: &'static str,
+}
+
+impl TuningKey {
+    /// Key for `plan` as configured, on the device described by `properties`.
+    pub fn new(properties: &vk::PhysicalDeviceProperties, plan: &VkFft) -> Self {
+        let config = &plan.config;
+        Self {
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            driver_version: properties.driver_version,
+            dims: config.FFTdim,
+            size: config.size,
+            batches: config.numberBatches.max(1),
+            r2c: config.performR2C != 0,
+            dct: config.performDCT,
+            precision: precision_label(config),
+        }
+    }
+}
+
+/// Fastest [`TuningOptions`] found per [`TuningKey`].
+///
+/// The text form has one entry per line: the key as `vendor`, `device`,
+/// `driver`, `dims`, `size`, `batches`, `r2c`, `dct` and `precision` fields,
+/// followed by the options in [`TuningOptions::write_fields`] form. Empty
+/// lines and lines starting with `#` are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TuningCache {
+    entries: BTreeMap<TuningKey, TuningOptions>,
+}
+
+impl TuningCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &TuningKey) -> Option<&TuningOptions> {
+        self.entries.get(key)
+    }
+
+    /// Record `options` for `key`, returning the previous entry.
+    pub fn insert(&mut self, key: TuningKey, options: TuningOptions) -> Option<TuningOptions> {
+        self.entries.insert(key, options)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&TuningKey, &TuningOptions)> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(feature = "std")]
+impl TuningCache {
+    /// Read a cache written by [`TuningCache::save`]. A missing file is an
+    /// empty cache, so the first run of an application can tune and save.
+    ///
+    /// # Errors
+    ///
+    /// I/O errors other than a missing file, and `InvalidData` for text
+    /// [`TuningCache::from_str`] rejects.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(err) => return Err(err),
+        };
+        text.parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Write the cache's text form to `path`, replacing the file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for TuningCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, options) in &self.entries {
+            let [x, y, z, w] = key.size;
+            write!(
+                f,
+                "vendor={} device={} driver={} dims={} size={x},{y},{z},{w} batches={} r2c={} dct={} precision={}",
+                key.vendor_id,
+                key.device_id,
+                key.driver_version,
+                key.dims,
+                key.batches,
+                key.r2c as u8,
+                key.dct,
+                key.precision,
+            )?;
+            if *options != TuningOptions::default() {
+                f.write_str(" ")?;
+                options.write_fields(f)?;
+            }
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TuningCache {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut cache = Self::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, options) = parse_entry(line)?;
+            cache.insert(key, options);
+        }
+        Ok(cache)
+    }
+}
+
+fn parse_entry(line: &str) -> Result<(TuningKey, TuningOptions), &'static str> {
+    const BAD_KEY: &str = "TuningCache: malformed key";
+    let mut key = TuningKey {
+        vendor_id: 0,
+        device_id: 0,
+        driver_version: 0,
+        dims: 0,
+        size: [1; 4],
+        batches: 1,
+        r2c: false,
+        dct: 0,
+        precision: "",
+    };
+    let mut options = TuningOptions::default();
+    let mut seen = 0u32;
+
+    for field in line.split_whitespace() {
+        let (name, value) = field.split_once('=').ok_or(BAD_KEY)?;
+        let number = || value.parse::<u64>().map_err(|_| BAD_KEY);
+        let id = || value.parse::<u32>().map_err(|_| BAD_KEY);
+        match name {
+            "vendor" => key.vendor_id = id()?,
+            "device" => key.device_id = id()?,
+            "driver" => key.driver_version = id()?,
+            "dims" => key.dims = number()?,
+            "batches" => key.batches = number()?,
+            "dct" => key.dct = number()?,
+            "r2c" => key.r2c = number()? != 0,
+            "size" => {
+                let mut parts = value.split(',');
+                for slot in &mut key.size {
+                    *slot = parts
+                        .next()
+                        .and_then(|part| part.parse().ok())
+                        .ok_or(BAD_KEY)?;
+                }
+            }
+            "precision" => {
+                key.precision = PRECISION_LABELS
+                    .into_iter()
+                    .find(|&label| label == value)
+                    .ok_or("TuningCache: unknown precision")?;
+            }
+            _ => {
+                if !options.parse_field(name, value)? {
+                    return Err("TuningCache: unknown field");
+                }
+                continue;
+            }
+        }
+        seen += 1;
+    }
+    if seen < 9 {
+        return Err("TuningCache: entry is missing key fields");
+    }
+    options.validate()?;
+    Ok((key, options))
+}
+
+/// Outcome of [`AutoTuner::tune`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuningResult {
+    pub key: TuningKey,
+    /// Fastest options found; all defaults if nothing beat VkFFT's choice.
+    pub options: TuningOptions,
+    /// Median GPU time of one forward transform with `options`.
+    pub duration: Duration,
+    /// Median GPU time with VkFFT's defaults.
+    pub default_duration: Duration,
+    /// Candidates measured, including the defaults.
+    pub evaluated: usize,
+    /// Candidates refused by `set_tuning`, `initialize` or `append`.
+    pub rejected: usize,
+}
+
+/// Benchmarks [`TuningOptions`] on a [`DeviceContext`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoTuner {
+    pub space: TuningSpace,
+    /// Untimed transforms run before measuring each candidate.
+    pub warmup: u32,
+    /// Timed transforms per candidate.
+    pub iterations: u32,
+}
+
+impl Default for AutoTuner {
+    fn default() -> Self {
+        Self {
+            space: TuningSpace::default(),
+            warmup: 3,
+            iterations: 20,
+        }
+    }
+}
+
+impl AutoTuner {
+    /// Find the fastest options for the plan `configure` sets up.
+    ///
+    /// `configure` is called on a fresh plan from `ctx` for every candidate
+    /// and must set dimensions, precision and buffers, but not tuning
+    /// options. The buffers' contents are overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns errors from `configure`, Vulkan failures, and the
+    /// `initialize` error if VkFFT rejects the plan with default options.
+    /// Profiling requires timestamp support on the context's queue family.
+    pub fn tune<F>(&self, ctx: &DeviceContext, mut configure: F) -> Result<TuningResult, VkfftError>
+    where
+        F: FnMut(&mut VkFft) -> Result<(), VkfftError>,
+    {
+        if self.iterations == 0 {
+            return Err(VkfftError::InvalidArgument(
+                "AutoTuner: iterations must be at least 1",
+            ));
+        }
+        let key = plan_key(ctx, &mut configure)?;
+        let default_duration = self.measure(ctx, &mut configure, &TuningOptions::default())?;
+
+        let mut best = (TuningOptions::default(), default_duration);
+        let mut evaluated = 1;
+        let mut rejected = 0;
+        for knob in 0..TuningSpace::KNOBS {
+            let base = best.0;
+            for candidate in self.space.variants(knob, &base) {
+                if candidate == base {
+                    continue;
+                }
+                match self.measure(ctx, &mut configure, &candidate) {
+                    Ok(duration) => {
+                        evaluated += 1;
+                        if duration < best.1 {
+                            best = (candidate, duration);
+                        }
+                    }
+                    // Limits the device rejects surface from `set_tuning` or VkFFT.
+                    Err(VkfftError::Vkfft(_) | VkfftError::InvalidArgument(_)) => rejected += 1,
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        Ok(TuningResult {
+            key,
+            options: best.0,
+            duration: best.1,
+            default_duration,
+            evaluated,
+            rejected,
+        })
+    }
+
+    /// Look the plan up in `cache`, tuning and recording it on a miss.
+    pub fn tune_cached<F>(
+        &self,
+        ctx: &DeviceContext,
+        cache: &mut TuningCache,
+        mut configure: F,
+    ) -> Result<TuningOptions, VkfftError>
+    where
+        F: FnMut(&mut VkFft) -> Result<(), VkfftError>,
+    {
+        let key = plan_key(ctx, &mut configure)?;
+        if let Some(options) = cache.get(&key) {
+            return Ok(*options);
+        }
+        let result = self.tune(ctx, configure)?;
+        cache.insert(result.key, result.options);
+        Ok(result.options)
+    }
+
+    /// Median GPU time of one forward transform with `options`.
+    fn measure<F>(
+        &self,
+        ctx: &DeviceContext,
+        configure: &mut F,
+        options: &TuningOptions,
+    ) -> Result<Duration, VkfftError>
+    where
+        F: FnMut(&mut VkFft) -> Result<(), VkfftError>,
+    {
+        let device = ctx.device();
+        let mut plan = ctx.create_plan();
+        configure(&mut plan)?;
+        plan.set_tuning(options)?;
+        plan.initialize()?;
+
+        ctx.run(|cmd| {
+            for _ in 0..self.warmup {
+                plan.append(cmd, -1)?;
+                ctx.compute_barrier(cmd);
+            }
+            Ok(())
+        })?;
+
+        unsafe {
+            plan.enable_profiling(
+                ctx.instance(),
+                device,
+                ctx.queue_family_index(),
+                self.iterations,
+            )?;
+        }
+        ctx.run(|cmd| {
+            for i in 0..self.iterations {
+                if i > 0 {
+                    ctx.compute_barrier(cmd);
+                }
+                plan.append(cmd, -1)?;
+            }
+            Ok(())
+        })?;
+
+        let mut durations: Vec<Duration> = plan
+            .take_timings()?
+            .into_iter()
+            .map(|timing| timing.duration)
+            .collect();
+        durations.sort_unstable();
+        // The fence wait in `run` makes every timestamp available.
+        Ok(durations
+            .get(durations.len() / 2)
+            .copied()
+            .unwrap_or_default())
+    }
+}
+
+fn plan_key<F>(ctx: &DeviceContext, configure: &mut F) -> Result<TuningKey, VkfftError>
+where
+    F: FnMut(&mut VkFft) -> Result<(), VkfftError>,
+{
+    let mut probe = ctx.create_plan();
+    configure(&mut probe)?;
+    let properties = unsafe {
+        ctx.instance()
+            .get_physical_device_properties(ctx.physical_device())
+    };
+    Ok(TuningKey::new(&properties, &probe))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn walks_one_knob_at_a_time() {
+        let space = TuningSpace::default();
+        let base = TuningOptions {
+            coalesced_memory: Some(64),
+            ..Default::default()
+        };
+        let variants = space.variants(1, &base);
+        assert_eq!(variants.len(), 3);
+        assert!(variants
+            .iter()
+            .all(|options| options.coalesced_memory == Some(64) && options.aim_threads.is_some()));
+        assert_eq!(space.variants(4, &base)[1].lut, Some(true));
+    }
+
+    #[test]
+    fn cache_round_trips_through_text() {
+        let mut fft = VkFft::new();
+        fft.configure_dimensions(&[256, 256]);
+        fft.set_double_precision(true);
+        let properties = vk::PhysicalDeviceProperties {
+            vendor_id: 0x10de,
+            device_id: 0x2204,
+            driver_version: 7,
+            ..Default::default()
+        };
+        let key = TuningKey::new(&properties, &fft);
+        assert_eq!(key.precision, "double");
+        assert_eq!(key.batches, 1);
+
+        let mut cache = TuningCache::new();
+        cache.insert(
+            key.clone(),
+            TuningOptions {
+                aim_threads: Some(256),
+                lut: Some(true),
+                ..Default::default()
+            },
+        );
+        fft.configure_dimensions(&[1024]);
+        cache.insert(TuningKey::new(&properties, &fft), TuningOptions::default());
+
+        let text = cache.to_string();
+        assert!(text.contains(
+            "vendor=4318 device=8708 driver=7 dims=2 size=256,256,1,1 batches=1 r2c=0 dct=0 \
+             precision=double aim_threads=256 lut=1\n"
+        ));
+        let parsed: TuningCache = text.parse().unwrap();
+        assert_eq!(parsed, cache);
+        assert_eq!(parsed.get(&key).unwrap().aim_threads, Some(256));
+
+        assert!("vendor=1 device=2".parse::<TuningCache>().is_err());
+        assert!(text
+            .replace("precision=double", "precision=quad")
+            .parse::<TuningCache>()
+            .is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cache_round_trips_through_a_file() {
+        let path = std::env::temp_dir().join(std::format!(
+            "vkfft-tuning-cache-{}.txt",
+            std::process::id()
+        ));
+        assert!(TuningCache::load(&path).unwrap().is_empty());
+
+        let fft = VkFft::new();
+        let mut cache = TuningCache::new();
+        cache.insert(
+            TuningKey::new(&vk::PhysicalDeviceProperties::default(), &fft),
+            TuningOptions {
+                coalesced_memory: Some(64),
+                ..Default::default()
+            },
+        );
+        cache.save(&path).unwrap();
+        assert_eq!(TuningCache::load(&path).unwrap(), cache);
+
+        std::fs::write(&path, "vendor=1 device=2").unwrap();
+        let err = TuningCache::load(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,590 @@
+//! Headless VkFFT benchmark.
+//!
+//! Sweeps transform sizes, dimensions, batch counts and precisions on one
+//! physical device, times each configuration with Vulkan timestamp queries
+//! and prints GFLOPS and effective bandwidth.
+//!
+//! ```text
+//! cargo run --release --features typed --bin vkfft-bench -- \
+//!     --sizes 64..65536 --dims 1,2 --precision single,double --format csv
+//! ```
+
+use std::error::Error;
+use std::fmt::Write as _;
+use std::process::ExitCode;
+
+use ash::vk;
+use vkfft_bindings::context::DeviceBuffer;
+use vkfft_bindings::element::PrecisionFlags;
+use vkfft_bindings::vkfft::VkfftError;
+use vkfft_bindings::{DeviceContext, DeviceSelector, FftContext, Precision};
+
+const USAGE: &str = "\
+usage: vkfft-bench [options]
+
+  --list-devices          print the available physical devices and exit
+  --device <index|name>   device index or name substring (default: a discrete
+                          GPU, falling back to integrated, virtual and CPU
+                          devices)
+  --sizes <list>          per-axis lengths: comma list, `a..b` for powers of two
+                          from a to b (default: 64..1048576)
+  --dims <list>           number of transformed axes, 1-3 (default: 1)
+  --batch <list>          batch counts (default: 1)
+  --precision <list>      half (fp16 storage, fp32 compute), single, double
+                          (default: single)
+  --iterations <n>        timed transforms per configuration (default: 100)
+  --warmup <n>            untimed transforms before timing (default: 10)
+  --max-memory <MiB>      skip configurations allocating more (default: 1024)
+  --format <fmt>          table, csv or json (default: table)
+";
+
+#[derive(Copy, Clone, PartialEq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+struct Options {
+    list_devices: bool,
+    device: Option<String>,
+    sizes: Vec<u64>,
+    dims: Vec<usize>,
+    batches: Vec<u64>,
+    precisions: Vec<Precision>,
+    iterations: u32,
+    warmup: u32,
+    max_memory: u64,
+    format: Format,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            list_devices: false,
+            device: None,
+            sizes: powers_of_two(64, 1 << 20),
+            dims: vec![1],
+            batches: vec![1],
+            precisions: vec![Precision::Single],
+            iterations: 100,
+            warmup: 10,
+            max_memory: 1024 << 20,
+            format: Format::Table,
+        }
+    }
+}
+
+fn powers_of_two(from: u64, to: u64) -> Vec<u64> {
+    let mut sizes = Vec::new();
+    let mut n = from.max(1).next_power_of_two();
+    while n <= to {
+        sizes.push(n);
+        n *= 2;
+    }
+    sizes
+}
+
+fn parse_list<T>(value: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|item| parse(item.trim()).ok_or_else(|| format!("invalid value `{item}`")))
+        .collect()
+}
+
+fn parse_sizes(value: &str) -> Result<Vec<u64>, String> {
+    let mut sizes = Vec::new();
+    for item in value.split(',') {
+        if let Some((from, to)) = item.split_once("..") {
+            let from = from
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid size `{item}`"))?;
+            let to = to
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid size `{item}`"))?;
+            sizes.extend(powers_of_two(from, to));
+        } else {
+            let n: u64 = item
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid size `{item}`"))?;
+            if n == 0 {
+                return Err("sizes must be positive".into());
+            }
+            sizes.push(n);
+        }
+    }
+    Ok(sizes)
+}
+
+fn parse_precision(value: &str) -> Option<Precision> {
+    match value {
+        "half" | "f16" => Some(Precision::Half),
+        "single" | "f32" => Some(Precision::Single),
+        "double" | "f64" => Some(Precision::Double),
+        _ => None,
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            print!("{USAGE}");
+            std::process::exit(0);
+        }
+        if arg == "--list-devices" {
+            options.list_devices = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for `{arg}`"))?;
+        match arg.as_str() {
+            "--device" => options.device = Some(value),
+            "--sizes" => options.sizes = parse_sizes(&value)?,
+            "--dims" => {
+                options.dims =
+                    parse_list(&value, |v| v.parse().ok().filter(|d| (1..=3).contains(d)))?
+            }
+            "--batch" => {
+                options.batches = parse_list(&value, |v| v.parse().ok().filter(|&b| b > 0))?
+            }
+            "--precision" => options.precisions = parse_list(&value, parse_precision)?,
+            "--iterations" => {
+                options.iterations = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or("--iterations must be a positive integer")?
+            }
+            "--warmup" => options.warmup = value.parse().map_err(|_| "invalid --warmup")?,
+            "--max-memory" => {
+                let mib: u64 = value.parse().map_err(|_| "invalid --max-memory")?;
+                options.max_memory = mib << 20;
+            }
+            "--format" => {
+                options.format = match value.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => return Err(format!("unknown format `{value}`")),
+                }
+            }
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+    Ok(options)
+}
+
+/// Vulkan device owned by the benchmark.
+struct Bench {
+    ctx: FftContext,
+    max_buffer: u64,
+}
+
+impl Bench {
+    fn open(selector: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let selector = match selector {
+            Some(selector) => match selector.parse::<usize>() {
+                Ok(index) => DeviceSelector::Index(index),
+                Err(_) => DeviceSelector::Name(selector.to_string()),
+            },
+            None => DeviceSelector::HighPerformance,
+        };
+        let ctx = FftContext::new(selector).map_err(|err| -> Box<dyn Error> {
+            match err {
+                VkfftError::NoDevice => "no matching Vulkan device (see --list-devices)".into(),
+                err => err.into(),
+            }
+        })?;
+
+        let instance = ctx.instance();
+        let properties = unsafe { instance.get_physical_device_properties(ctx.physical_device()) };
+        let families =
+            unsafe { instance.get_physical_device_queue_family_properties(ctx.physical_device()) };
+        if families[ctx.queue_family_index() as usize].timestamp_valid_bits == 0 {
+            return Err("compute queue does not support timestamp queries".into());
+        }
+
+        Ok(Self {
+            max_buffer: properties.limits.max_storage_buffer_range as u64,
+            ctx,
+        })
+    }
+
+    fn ctx(&self) -> &DeviceContext {
+        &self.ctx
+    }
+
+    fn name(&self) -> &str {
+        self.ctx.name()
+    }
+
+    fn supports(&self, precision: Precision) -> bool {
+        let limits = self.ctx.limits();
+        match precision {
+            Precision::Half => limits.storage16,
+            Precision::Single => true,
+            Precision::Double => limits.float64,
+        }
+    }
+
+    /// Average GPU time of one forward transform in nanoseconds.
+    fn time_case(&self, case: &Case, options: &Options) -> Result<f64, VkfftError> {
+        let ctx = self.ctx();
+        let device = ctx.device();
+        // Half storage is computed in fp32; fp16 arithmetic needs shaderFloat16.
+        // VkFFT then reads and writes fp16 buffers around an fp32 one.
+        let half_memory = case.precision == Precision::Half;
+        let working_bytes = case
+            .working_bytes()
+            .ok_or(VkfftError::InvalidArgument("buffer size overflows"))?;
+        let buffer = ctx.create_device_buffer(working_bytes)?;
+        let formatted = if half_memory {
+            Some([
+                ctx.create_device_buffer(case.bytes)?,
+                ctx.create_device_buffer(case.bytes)?,
+            ])
+        } else {
+            None
+        };
+
+        let mut plan = ctx.create_plan();
+        let compute = if half_memory {
+            Precision::Single
+        } else {
+            case.precision
+        };
+        plan.set_precision_flags(PrecisionFlags::new(case.precision, compute)?);
+        plan.configure_dimensions(&case.sizes);
+        plan.set_batch_count(case.batch);
+        match &formatted {
+            Some([input, output]) => plan.configure_formatted_buffers(
+                input.raw(),
+                input.size(),
+                buffer.raw(),
+                buffer.size(),
+                output.raw(),
+                output.size(),
+            )?,
+            None => {
+                plan.configure_buffers(&[buffer.raw()], None)?;
+                plan.set_buffer_sizes(&[buffer.size()]);
+            }
+        }
+        plan.initialize()?;
+
+        ctx.run(|cmd| {
+            clear(device, cmd, &buffer);
+            for input in formatted.iter().flatten() {
+                clear(device, cmd, input);
+            }
+            for _ in 0..options.warmup {
+                plan.append(cmd, -1)?;
+                ctx.compute_barrier(cmd);
+            }
+            Ok(())
+        })?;
+
+        unsafe {
+            plan.enable_profiling(
+                ctx.instance(),
+                device,
+                ctx.queue_family_index(),
+                options.iterations,
+            )?;
+        }
+        ctx.run(|cmd| {
+            for i in 0..options.iterations {
+                if i > 0 {
+                    ctx.compute_barrier(cmd);
+                }
+                plan.append(cmd, -1)?;
+            }
+            Ok(())
+        })?;
+
+        // The fence wait in `run` makes every timestamp available.
+        let timings = plan.take_timings()?;
+        let total: f64 = timings
+            .iter()
+            .map(|timing| timing.duration.as_nanos() as f64)
+            .sum();
+        Ok(total / timings.len().max(1) as f64)
+    }
+}
+
+fn clear(device: &ash::Device, cmd: vk::CommandBuffer, buffer: &DeviceBuffer<'_>) {
+    let barrier = vk::MemoryBarrier {
+        s_type: vk::StructureType::MEMORY_BARRIER,
+        src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+        dst_access_mask: vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+        ..Default::default()
+    };
+    unsafe {
+        device.cmd_fill_buffer(cmd, buffer.raw(), 0, vk::WHOLE_SIZE, 0);
+        device.cmd_pipeline_barrier(
+            cmd,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            vk::DependencyFlags::empty(),
+            &[barrier],
+            &[],
+            &[],
+        );
+    }
+}
+
+/// One point of the sweep.
+struct Case {
+    sizes: Vec<u64>,
+    batch: u64,
+    precision: Precision,
+    /// Buffer size for interleaved complex data at the storage precision.
+    bytes: u64,
+}
+
+impl Case {
+    /// `None` if the buffer size does not fit in a `u64`.
+    fn new(sizes: Vec<u64>, batch: u64, precision: Precision) -> Option<Self> {
+        let scalar = match precision {
+            Precision::Half => 2,
+            Precision::Single => 4,
+            Precision::Double => 8,
+        };
+        let bytes = sizes
+            .iter()
+            .try_fold(batch.checked_mul(2 * scalar)?, |bytes, &n| {
+                bytes.checked_mul(n)
+            })?;
+        Some(Self {
+            sizes,
+            batch,
+            precision,
+            bytes,
+        })
+    }
+
+    /// Size of the buffer VkFFT transforms in: half storage is computed in
+    /// an fp32 buffer of twice the size.
+    fn working_bytes(&self) -> Option<u64> {
+        match self.precision {
+            Precision::Half => self.bytes.checked_mul(2),
+            _ => Some(self.bytes),
+        }
+    }
+
+    /// Device memory the case allocates, including the fp16 input and
+    /// output buffers around the working buffer for half storage.
+    fn allocated_bytes(&self) -> Option<u64> {
+        match self.precision {
+            Precision::Half => self
+                .working_bytes()?
+                .checked_add(self.bytes.checked_mul(2)?),
+            _ => Some(self.bytes),
+        }
+    }
+
+    /// Conventional `5 N log2 N` flop count over the whole batch.
+    fn flops(&self) -> f64 {
+        let n = self.sizes.iter().product::<u64>() as f64;
+        5.0 * n * n.log2() * self.batch as f64
+    }
+
+    fn shape(&self) -> String {
+        shape(&self.sizes)
+    }
+}
+
+struct Measurement {
+    case: Case,
+    time_ns: f64,
+}
+
+impl Measurement {
+    fn gflops(&self) -> f64 {
+        self.case.flops() / self.time_ns
+    }
+
+    /// Effective bandwidth assuming the buffer is read and written once.
+    fn bandwidth_gbs(&self) -> f64 {
+        2.0 * self.case.bytes as f64 / self.time_ns
+    }
+}
+
+fn shape(sizes: &[u64]) -> String {
+    sizes
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join("x")
+}
+
+/// Half storage is always computed in fp32; fp16 arithmetic is not
+/// benchmarked.
+fn precision_label(precision: Precision) -> &'static str {
+    match precision {
+        Precision::Half => "half/fp32-compute",
+        Precision::Single => "single",
+        Precision::Double => "double",
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn print_header(format: Format, device: &str) {
+    match format {
+        Format::Table => {
+            println!("device: {device}");
+            println!(
+                "{:>4} {:>20} {:>6} {:>17} {:>12} {:>10} {:>10}",
+                "dims", "size", "batch", "precision", "time (ms)", "GFLOPS", "GB/s"
+            );
+        }
+        Format::Csv => {
+            println!("device,dims,size,batch,precision,iterations,time_ms,gflops,bandwidth_gbs")
+        }
+        Format::Json => println!("{{\"device\": {}, \"results\": [", json_string(device)),
+    }
+}
+
+fn print_row(format: Format, device: &str, m: &Measurement, iterations: u32, first: bool) {
+    let case = &m.case;
+    let precision = precision_label(case.precision);
+    let time_ms = m.time_ns * 1e-6;
+    match format {
+        Format::Table => println!(
+            "{:>4} {:>20} {:>6} {:>17} {:>12.4} {:>10.1} {:>10.1}",
+            case.sizes.len(),
+            case.shape(),
+            case.batch,
+            precision,
+            time_ms,
+            m.gflops(),
+            m.bandwidth_gbs()
+        ),
+        Format::Csv => println!(
+            "{},{},{},{},{},{},{:.6},{:.3},{:.3}",
+            json_string(device),
+            case.sizes.len(),
+            case.shape(),
+            case.batch,
+            precision,
+            iterations,
+            time_ms,
+            m.gflops(),
+            m.bandwidth_gbs()
+        ),
+        Format::Json => println!(
+            "{}  {{\"dims\": {}, \"size\": [{}], \"batch\": {}, \"precision\": \"{}\", \
+             \"iterations\": {}, \"time_ms\": {:.6}, \"gflops\": {:.3}, \"bandwidth_gbs\": {:.3}}}",
+            if first { "" } else { "," },
+            case.sizes.len(),
+            case.sizes
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            case.batch,
+            precision,
+            iterations,
+            time_ms,
+            m.gflops(),
+            m.bandwidth_gbs()
+        ),
+    }
+}
+
+fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    if options.list_devices {
+        for device in FftContext::devices()? {
+            println!(
+                "{}: {} ({:?})",
+                device.index, device.name, device.device_type
+            );
+        }
+        return Ok(());
+    }
+
+    let bench = Bench::open(options.device.as_deref())?;
+    print_header(options.format, bench.name());
+
+    let mut first = true;
+    for &dims in &options.dims {
+        for &n in &options.sizes {
+            for &batch in &options.batches {
+                for &precision in &options.precisions {
+                    let sizes = vec![n; dims];
+                    let label =
+                        format!("{} x{batch} {}", shape(&sizes), precision_label(precision));
+                    if !bench.supports(precision) {
+                        eprintln!("skipping {label}: precision not supported by the device");
+                        continue;
+                    }
+                    let Some(case) = Case::new(sizes, batch, precision) else {
+                        eprintln!("skipping {label}: buffer size overflows");
+                        continue;
+                    };
+                    let fits = case
+                        .working_bytes()
+                        .is_some_and(|bytes| bytes <= bench.max_buffer)
+                        && case
+                            .allocated_bytes()
+                            .is_some_and(|bytes| bytes <= options.max_memory);
+                    if !fits {
+                        eprintln!("skipping {label}: buffer exceeds the memory limit");
+                        continue;
+                    }
+                    match bench.time_case(&case, &options) {
+                        Ok(time_ns) => {
+                            let m = Measurement { case, time_ns };
+                            print_row(options.format, bench.name(), &m, options.iterations, first);
+                            first = false;
+                        }
+                        Err(err) => eprintln!("skipping {label}: {err}"),
+                    }
+                }
+            }
+        }
+    }
+
+    if options.format == Format::Json {
+        println!("]}}");
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("vkfft-bench: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("vkfft-bench: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
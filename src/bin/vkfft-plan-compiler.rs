@@ -0,0 +1,404 @@
+//! Ahead-of-time VkFFT plan compiler.
+//!
+//! Reads a list of FFT configurations, initializes each one on the target
+//! device and writes their compiled kernels to a plan bundle
+//! (`vkfft_bindings::PlanBundle`). At run time, plans configured the same
+//! way load their kernels from the bundle instead of compiling shaders.
+//!
+//! ```text
+//! cargo run --release --features plan-compiler --bin vkfft-plan-compiler -- \
+//!     --device "RTX 4090" plans.toml -o plans.vkfftb
+//! ```
+//!
+//! The input is TOML (`[[plan]]` tables) or JSON (`{"plans": [...]}`),
+//! chosen by file extension. Each plan is a serialized
+//! `vkfft_bindings::PlanDescription` plus an optional `name`; stride arrays
+//! shorter than four axes are padded with their last value:
+//!
+//! ```toml
+//! device = "RTX 4090"        # optional; --device takes precedence
+//!
+//! [[plan]]
+//! name = "spectrum"          # optional label stored in the bundle
+//! size = [4096, 4096]        # 1-4 axes, contiguous axis first
+//! kind = "c2c"               # c2c, r2c, dct1 .. dct4
+//! precision = "single"       # storage: half, single, double
+//! compute = "double"         # optional, defaults to the storage precision
+//! batch = 8
+//! buffer_stride = [4096, 16777216]   # optional
+//! ```
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use serde::Deserialize;
+use serde_json::Value;
+use vkfft_bindings::plan_bundle::BundleDevice;
+use vkfft_bindings::vkfft::VkfftError;
+use vkfft_bindings::{
+    DeviceContext, DeviceSelector, FftContext, PlanBundle, PlanDescription, Precision,
+    TransformKind,
+};
+
+const USAGE: &str = "\
+usage: vkfft-plan-compiler [options] <plans.toml|plans.json> -o <bundle>
+
+  --list-devices          print the available physical devices and exit
+  --device <index|name>   device index or name substring (default: the input's
+                          `device`, else a discrete GPU, falling back to
+                          integrated, virtual and CPU devices)
+  -o, --output <path>     bundle to write
+";
+
+#[derive(Default)]
+struct Options {
+    list_devices: bool,
+    device: Option<String>,
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => {
+                print!("{USAGE}");
+                std::process::exit(0);
+            }
+            "--list-devices" => options.list_devices = true,
+            "--device" | "-o" | "--output" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                if arg == "--device" {
+                    options.device = Some(value);
+                } else {
+                    options.output = Some(value.into());
+                }
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ if options.input.is_none() => options.input = Some(arg.into()),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    Ok(options)
+}
+
+/// The top level of an input file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Input {
+    device: Option<String>,
+    /// `[[plan]]` tables in TOML, the `plans` array in JSON.
+    #[serde(default, alias = "plan")]
+    plans: Vec<Value>,
+}
+
+/// One configuration to compile.
+#[derive(Clone, Debug, PartialEq)]
+struct PlanSpec {
+    name: Option<String>,
+    description: PlanDescription,
+}
+
+impl PlanSpec {
+    fn from_value(mut value: Value) -> Result<Self, String> {
+        let Some(fields) = value.as_object_mut() else {
+            return Err("expected a table".into());
+        };
+        let name = match fields.remove("name") {
+            None => None,
+            Some(Value::String(name)) => Some(name),
+            Some(_) => return Err("`name` must be a string".into()),
+        };
+        for key in ["buffer_stride", "input_stride", "output_stride"] {
+            if let Some(Value::Array(strides)) = fields.get_mut(key) {
+                if let Some(last) = strides.last().filter(|_| strides.len() < 4).cloned() {
+                    strides.resize(4, last);
+                }
+            }
+        }
+        let description: PlanDescription =
+            serde_json::from_value(value).map_err(|err| err.to_string())?;
+        if description.size.is_empty() {
+            return Err("`size` is required".into());
+        }
+        if description.size.contains(&0) {
+            return Err("sizes must be positive".into());
+        }
+        Ok(Self { name, description })
+    }
+
+    fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let description = &self.description;
+        let kind = match description.kind {
+            TransformKind::C2c => "c2c",
+            TransformKind::R2c => "r2c",
+            TransformKind::Dct1 => "dct1",
+            TransformKind::Dct2 => "dct2",
+            TransformKind::Dct3 => "dct3",
+            TransformKind::Dct4 => "dct4",
+        };
+        let shape = description
+            .size
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join("x");
+        format!(
+            "{kind} {shape} x{} {}",
+            description.batch,
+            precision_name(description.precision)
+        )
+    }
+
+    /// Bytes of a buffer large enough for every supported layout: complex
+    /// elements, and the largest extent any stride describes.
+    fn buffer_bytes(&self) -> u64 {
+        let description = &self.description;
+        let scalar = match description.precision {
+            Precision::Half => 2,
+            Precision::Single => 4,
+            Precision::Double => 8,
+        };
+        let outer = description.size.len() - 1;
+        let per_batch = [
+            description.buffer_stride,
+            description.input_stride,
+            description.output_stride,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|stride| stride[outer])
+        .fold(description.size.iter().product::<u64>(), u64::max);
+        per_batch * description.batch * 2 * scalar
+    }
+}
+
+fn precision_name(precision: Precision) -> &'static str {
+    match precision {
+        Precision::Half => "half",
+        Precision::Single => "single",
+        Precision::Double => "double",
+    }
+}
+
+/// The input's default device and its plans.
+fn read_input(path: &Path) -> Result<(Option<String>, Vec<PlanSpec>), String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let input = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => parse_json(&text),
+        Some("toml") => parse_toml(&text),
+        _ => {
+            return Err(format!(
+                "{}: expected a .toml or .json file",
+                path.display()
+            ))
+        }
+    };
+    input
+        .and_then(plans_from_input)
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn parse_json(text: &str) -> Result<Input, String> {
+    serde_json::from_str(text).map_err(|err| err.to_string())
+}
+
+fn parse_toml(text: &str) -> Result<Input, String> {
+    toml::from_str(text).map_err(|err| err.to_string())
+}
+
+fn plans_from_input(input: Input) -> Result<(Option<String>, Vec<PlanSpec>), String> {
+    if input.plans.is_empty() {
+        return Err("no plans listed".into());
+    }
+    let plans = input
+        .plans
+        .into_iter()
+        .enumerate()
+        .map(|(index, plan)| {
+            PlanSpec::from_value(plan).map_err(|err| format!("plan {}: {err}", index + 1))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((input.device, plans))
+}
+
+fn compile(
+    ctx: &DeviceContext,
+    spec: &PlanSpec,
+    bundle: &mut PlanBundle,
+) -> Result<(), Box<dyn Error>> {
+    let description = &spec.description;
+    let bytes = spec.buffer_bytes();
+    // fp16 storage computed in fp32 runs out of place through an fp32 buffer.
+    let half_memory =
+        description.precision == Precision::Half && description.compute == Some(Precision::Single);
+    let buffer = ctx.create_device_buffer(if half_memory { 2 * bytes } else { bytes })?;
+    let formatted = if half_memory {
+        Some([
+            ctx.create_device_buffer(bytes)?,
+            ctx.create_device_buffer(bytes)?,
+        ])
+    } else {
+        None
+    };
+
+    let mut plan = ctx.create_plan_from(description)?;
+    match &formatted {
+        Some([input, output]) => plan.configure_formatted_buffers(
+            input.raw(),
+            input.size(),
+            buffer.raw(),
+            buffer.size(),
+            output.raw(),
+            output.size(),
+        )?,
+        None => {
+            plan.configure_buffers(&[buffer.raw()], None)?;
+            plan.set_buffer_sizes(&[buffer.size()]);
+        }
+    }
+    plan.set_plan_saving(true);
+    plan.initialize()?;
+    bundle.add(spec.label(), &plan)?;
+    Ok(())
+}
+
+fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    if options.list_devices {
+        for device in FftContext::devices()? {
+            println!(
+                "{}: {} ({:?})",
+                device.index, device.name, device.device_type
+            );
+        }
+        return Ok(());
+    }
+    let input = options.input.ok_or("missing input file")?;
+    let output = options.output.ok_or("missing --output")?;
+    let (input_device, specs) = read_input(&input)?;
+
+    let selector = match options.device.or(input_device) {
+        Some(selector) => match selector.parse::<usize>() {
+            Ok(index) => DeviceSelector::Index(index),
+            Err(_) => DeviceSelector::Name(selector),
+        },
+        None => DeviceSelector::HighPerformance,
+    };
+    let ctx = FftContext::new(selector).map_err(|err| -> Box<dyn Error> {
+        match err {
+            VkfftError::NoDevice => "no matching Vulkan device (see --list-devices)".into(),
+            err => err.into(),
+        }
+    })?;
+    let properties = unsafe {
+        ctx.instance()
+            .get_physical_device_properties(ctx.physical_device())
+    };
+    let mut bundle = PlanBundle::new(BundleDevice::from_properties(&properties));
+    eprintln!("compiling {} plans on {}", specs.len(), ctx.name());
+
+    for spec in &specs {
+        let label = spec.label();
+        compile(&ctx, spec, &mut bundle).map_err(|err| format!("{label}: {err}"))?;
+        eprintln!("  {label}");
+    }
+
+    std::fs::write(&output, bundle.to_bytes())
+        .map_err(|err| format!("{}: {err}", output.display()))?;
+    eprintln!(
+        "wrote {} plans for VkFFT {} to {}",
+        bundle.entries().len(),
+        vkfft_bindings::version(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("vkfft-plan-compiler: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("vkfft-plan-compiler: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+device = "RTX"   # default device
+
+[[plan]]
+name = "spectrum # 1"
+size = [4096, 4_096]
+batch = 8
+
+[[plan]]
+size = [1000]
+kind = "r2c"
+precision = "half"
+compute = "single"
+buffer_stride = [1002,]
+"#;
+
+    const JSON: &str = r#"{
+        "device": "RTX",
+        "plans": [
+            {"name": "spectrum # 1", "size": [4096, 4096], "batch": 8},
+            {"size": [1000], "kind": "r2c", "precision": "half", "compute": "single",
+             "buffer_stride": [1002]}
+        ]
+    }"#;
+
+    #[test]
+    fn toml_and_json_describe_the_same_plans() {
+        let (device, plans) = plans_from_input(parse_toml(TOML).unwrap()).unwrap();
+        assert_eq!(device.as_deref(), Some("RTX"));
+        assert_eq!(
+            plans_from_input(parse_json(JSON).unwrap()).unwrap(),
+            (device, plans.clone())
+        );
+
+        assert_eq!(plans[0].label(), "spectrum # 1");
+        assert_eq!(plans[0].description.size, [4096, 4096]);
+        assert_eq!(plans[0].buffer_bytes(), 4096 * 4096 * 8 * 8);
+        let description = &plans[1].description;
+        assert_eq!(description.kind, TransformKind::R2c);
+        assert_eq!(description.compute, Some(Precision::Single));
+        assert_eq!(description.buffer_stride, Some([1002; 4]));
+        assert_eq!(plans[1].label(), "r2c 1000 x1 half");
+        assert_eq!(plans[1].buffer_bytes(), 1002 * 2 * 2);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let plans = |text: &str| parse_json(text).and_then(plans_from_input);
+        assert!(plans(r#"{"plans": []}"#).is_err());
+        assert!(plans(r#"{"plans": [{"batch": 2}]}"#).is_err());
+        assert!(plans(r#"{"plans": [{"size": [64], "kind": "dct5"}]}"#).is_err());
+        assert!(plans(r#"{"plans": [{"size": [64.5]}]}"#).is_err());
+        assert!(plans(r#"{"plans": [{"size": [64], "sizes": [2]}]}"#).is_err());
+        assert!(plans(r#"{"plans": [{"size": [64]}]} x"#).is_err());
+        assert!(plans(r#"{"plans": [{"size": [64], "name": 1}]}"#).is_err());
+        assert!(plans(r#"{"plans": [{"size": [64]}], "devices": "RTX"}"#).is_err());
+        assert!(parse_toml("[plan]\nsize = [64]").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Bluestein padding controls.
+//!
+//! Lengths VkFFT cannot factor into its radices are computed as a
+//! convolution of at least `2n - 1` points. By default the padded length is
+//! picked automatically, which can land on a slow size for some primes.
+//! These setters expose VkFFT's custom padding table
+//! (`useCustomBluesteinPaddingPattern`, `primeSizes`, `paddedSizes`) and the
+//! largest radix allowed in automatically chosen padding
+//! (`fixMaxRadixBluestein`).
+
+use alloc::vec::Vec;
+
+use crate::radix::is_smooth;
+use crate::VkFft;
+
+/// One row of a custom Bluestein padding table.
+///
+/// Sequences longer than the previous row's `max_length` and up to this
+/// row's `max_length` are padded to `padded`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BluesteinPadding {
+    pub max_length: u64,
+    pub padded: u64,
+}
+
+impl VkFft {
+    /// Install a custom Bluestein padding table, or remove it with an empty
+    /// slice.
+    ///
+    /// # Errors
+    ///
+    /// Rows must be sorted by strictly increasing `max_length`, every `padded`
+    /// must hold the `2 * max_length - 1` point convolution and must factor
+    /// into VkFFT's radices (2 to 13).
+    pub fn set_bluestein_padding(
+        &mut self,
+        table: &[BluesteinPadding],
+    ) -> Result<(), &'static str> {
+        for (i, row) in table.iter().enumerate() {
+            if row.max_length == 0 {
+                return Err("set_bluestein_padding: max_length must be positive");
+            }
+            if i > 0 && row.max_length <= table[i - 1].max_length {
+                return Err("set_bluestein_padding: max_length must be strictly increasing");
+            }
+            // A convolution too long for u64 cannot fit in `padded` either.
+            let convolution = row.max_length.checked_mul(2).map(|n| n - 1);
+            if convolution.is_none_or(|n| row.padded < n) {
+                return Err("set_bluestein_padding: padded is shorter than 2 * max_length - 1");
+            }
+            if !is_smooth(row.padded, 13) {
+                return Err("set_bluestein_padding: padded must only have prime factors up to 13");
+            }
+        }
+
+        self.bluestein_primes = table.iter().map(|row| row.max_length).collect::<Vec<_>>();
+        self.bluestein_padded = table.iter().map(|row| row.padded).collect::<Vec<_>>();
+        if table.is_empty() {
+            self.config.useCustomBluesteinPaddingPattern = 0;
+            self.config.primeSizes = core::ptr::null_mut();
+            self.config.paddedSizes = core::ptr::null_mut();
+        } else {
+            self.config.useCustomBluesteinPaddingPattern = table.len() as u64;
+            self.config.primeSizes = self.bluestein_primes.as_mut_ptr();
+            self.config.paddedSizes = self.bluestein_padded.as_mut_ptr();
+        }
+        Ok(())
+    }
+
+    /// Limit the primes VkFFT may use when it picks a Bluestein padding on
+    /// its own; `None` restores VkFFT's default (2 for small systems, 7 for
+    /// large ones). Lower limits give faster but longer padded sequences.
+    ///
+    /// # Errors
+    ///
+    /// `max_radix` must be between 2 and 13.
+    pub fn set_max_radix_bluestein(&mut self, max_radix: Option<u64>) -> Result<(), &'static str> {
+        match max_radix {
+            Some(radix) if !(2..=13).contains(&radix) => {
+                Err("set_max_radix_bluestein: radix must be between 2 and 13")
+            }
+            _ => {
+                self.config.fixMaxRadixBluestein = max_radix.unwrap_or(0);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installs_and_clears_padding_table() {
+        let mut fft = VkFft::new();
+        let table = [
+            BluesteinPadding {
+                max_length: 1009,
+                padded: 2048,
+            },
+            BluesteinPadding {
+                max_length: 4099,
+                padded: 8232,
+            },
+        ];
+        fft.set_bluestein_padding(&table).unwrap();
+        assert_eq!(fft.config.useCustomBluesteinPaddingPattern, 2);
+        assert_eq!(unsafe { *fft.config.paddedSizes.add(1) }, 8232);
+        assert_eq!(unsafe { *fft.config.primeSizes }, 1009);
+
+        fft.set_bluestein_padding(&[]).unwrap();
+        assert_eq!(fft.config.useCustomBluesteinPaddingPattern, 0);
+        assert!(fft.config.primeSizes.is_null());
+    }
+
+    #[test]
+    fn rejects_invalid_padding() {
+        let mut fft = VkFft::new();
+        let row = |max_length, padded| BluesteinPadding { max_length, padded };
+
+        // Too short for the convolution.
+        assert!(fft.set_bluestein_padding(&[row(1009, 2016)]).is_err());
+        assert!(fft
+            .set_bluestein_padding(&[row(u64::MAX / 2 + 1, 1 << 63)])
+            .is_err());
+        // 2 * 1021 is not a radix-friendly length.
+        assert!(fft.set_bluestein_padding(&[row(1009, 2 * 1021)]).is_err());
+        // Not sorted.
+        assert!(fft
+            .set_bluestein_padding(&[row(4099, 8232), row(1009, 2048)])
+            .is_err());
+        assert_eq!(fft.config.useCustomBluesteinPaddingPattern, 0);
+
+        assert!(fft.set_max_radix_bluestein(Some(17)).is_err());
+        fft.set_max_radix_bluestein(Some(5)).unwrap();
+        assert_eq!(fft.config.fixMaxRadixBluestein, 5);
+    }
+}
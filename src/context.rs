@@ -0,0 +1,434 @@
+//! Device context that runs plans end to end.
+//!
+//! [`VkFft`] only records dispatches into command buffers the caller manages.
+//! [`DeviceContext`] adds the pieces needed to execute a transform on its own:
+//! a command pool and fence for one-shot submissions and host-visible buffers
+//! for moving data to and from the device.
+
+use alloc::boxed::Box;
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use ash::vk;
+
+use crate::vkfft::{VkFft, VkfftError};
+
+/// Vulkan device plus the objects needed to submit and wait on plans.
+///
+/// The instance and device are borrowed from the caller and are not destroyed
+/// when the context is dropped; only the command pool and fence are. Plans
+/// and buffers created by the context borrow it, so they cannot outlive it.
+///
+/// The context is `Send` but not `Sync`: [`DeviceContext::run`] and plan
+/// initialization use its one command pool, queue and fence without locking,
+/// so each thread needs its own context.
+pub struct DeviceContext {
+    instance: ash::Instance,
+    device: ash::Device,
+    physical_device: vk::PhysicalDevice,
+    queue: vk::Queue,
+    queue_family_index: u32,
+    command_pool: vk::CommandPool,
+    fence: vk::Fence,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl DeviceContext {
+    /// Create a context on an existing device.
+    ///
+    /// # Errors
+    /// Returns a Vulkan error if the command pool or fence cannot be created.
+    ///
+    /// # Safety
+    /// `physical_device` must come from `instance` and `device` from
+    /// `physical_device`. `queue` must belong to `queue_family_index` of
+    /// `device`, the family must support compute, and `instance`/`device` must
+    /// outlive the context. Nothing else may use `queue` while the context
+    /// submits to it.
+    pub unsafe fn new(
+        instance: ash::Instance,
+        device: ash::Device,
+        physical_device: vk::PhysicalDevice,
+        queue: vk::Queue,
+        queue_family_index: u32,
+    ) -> Result<Self, VkfftError> {
+        let command_pool_info = vk::CommandPoolCreateInfo {
+            s_type: vk::StructureType::COMMAND_POOL_CREATE_INFO,
+            queue_family_index,
+            flags: vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER,
+            ..Default::default()
+        };
+        let command_pool = device.create_command_pool(&command_pool_info, None)?;
+
+        let fence_info = vk::FenceCreateInfo {
+            s_type: vk::StructureType::FENCE_CREATE_INFO,
+            ..Default::default()
+        };
+        let fence = match device.create_fence(&fence_info, None) {
+            Ok(fence) => fence,
+            Err(err) => {
+                device.destroy_command_pool(command_pool, None);
+                return Err(err.into());
+            }
+        };
+
+        Ok(Self {
+            instance,
+            device,
+            physical_device,
+            queue,
+            queue_family_index,
+            command_pool,
+            fence,
+            _not_sync: PhantomData,
+        })
+    }
+
+    pub fn instance(&self) -> &ash::Instance {
+        &self.instance
+    }
+
+    pub fn device(&self) -> &ash::Device {
+        &self.device
+    }
+
+    pub fn physical_device(&self) -> vk::PhysicalDevice {
+        self.physical_device
+    }
+
+    pub fn queue(&self) -> vk::Queue {
+        self.queue
+    }
+
+    pub fn queue_family_index(&self) -> u32 {
+        self.queue_family_index
+    }
+
+    /// Create a plan bound to this context's device, queue, pool and fence,
+    /// with the device's limits detected (see
+    /// [`VkFft::configure_vulkan_with_instance`]).
+    ///
+    /// The plan is boxed because VkFFTConfiguration stores pointers into it.
+    pub fn create_plan(&self) -> Plan<'_> {
+        let mut fft = Box::new(VkFft::new());
+        // `new` requires the instance and physical device to match.
+        unsafe {
+            fft.configure_vulkan_with_instance(
+                &self.instance,
+                self.physical_device,
+                self.device.handle(),
+                self.queue,
+                self.command_pool,
+            );
+        }
+        fft.configure_fence(self.fence);
+        Plan {
+            fft,
+            _ctx: PhantomData,
+        }
+    }
+
+    /// Allocate a host-visible, host-coherent storage buffer of `size` bytes.
+    pub fn create_host_buffer(&self, size: vk::DeviceSize) -> Result<HostBuffer<'_>, VkfftError> {
+        let (buffer, memory) = self.allocate_buffer(
+            size,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+        Ok(HostBuffer {
+            device: self.device.clone(),
+            buffer,
+            memory,
+            size,
+            _ctx: PhantomData,
+        })
+    }
+
+    /// Allocate a device-local storage buffer of `size` bytes.
+    ///
+    /// The contents are undefined until written by a transfer or a transform.
+    pub fn create_device_buffer(
+        &self,
+        size: vk::DeviceSize,
+    ) -> Result<DeviceBuffer<'_>, VkfftError> {
+        let (buffer, memory) = self.allocate_buffer(size, vk::MemoryPropertyFlags::DEVICE_LOCAL)?;
+        Ok(DeviceBuffer {
+            device: self.device.clone(),
+            buffer,
+            memory,
+            size,
+            _ctx: PhantomData,
+        })
+    }
+
+    fn allocate_buffer(
+        &self,
+        size: vk::DeviceSize,
+        required: vk::MemoryPropertyFlags,
+    ) -> Result<(vk::Buffer, vk::DeviceMemory), VkfftError> {
+        let buffer_info = vk::BufferCreateInfo {
+            s_type: vk::StructureType::BUFFER_CREATE_INFO,
+            size,
+            usage: vk::BufferUsageFlags::STORAGE_BUFFER
+                | vk::BufferUsageFlags::TRANSFER_SRC
+                | vk::BufferUsageFlags::TRANSFER_DST,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        };
+        let buffer = unsafe { self.device.create_buffer(&buffer_info, None)? };
+
+        let requirements = unsafe { self.device.get_buffer_memory_requirements(buffer) };
+        let properties = unsafe {
+            self.instance
+                .get_physical_device_memory_properties(self.physical_device)
+        };
+        let Some(memory_type_index) =
+            find_memory_type_index(&properties, requirements.memory_type_bits, required)
+        else {
+            unsafe { self.device.destroy_buffer(buffer, None) };
+            return Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY.into());
+        };
+
+        let allocation_info = vk::MemoryAllocateInfo {
+            s_type: vk::StructureType::MEMORY_ALLOCATE_INFO,
+            allocation_size: requirements.size,
+            memory_type_index,
+            ..Default::default()
+        };
+        let memory = match unsafe { self.device.allocate_memory(&allocation_info, None) } {
+            Ok(memory) => memory,
+            Err(err) => {
+                unsafe { self.device.destroy_buffer(buffer, None) };
+                return Err(err.into());
+            }
+        };
+        if let Err(err) = unsafe { self.device.bind_buffer_memory(buffer, memory, 0) } {
+            unsafe {
+                self.device.destroy_buffer(buffer, None);
+                self.device.free_memory(memory, None);
+            }
+            return Err(err.into());
+        }
+        Ok((buffer, memory))
+    }
+
+    /// Record a barrier making compute shader writes visible to later compute
+    /// work, e.g. between transforms recorded back to back.
+    ///
+    /// `command_buffer` must be recording, such as the one passed to
+    /// [`DeviceContext::run`]'s closure.
+    pub fn compute_barrier(&self, command_buffer: vk::CommandBuffer) {
+        let barrier = vk::MemoryBarrier {
+            s_type: vk::StructureType::MEMORY_BARRIER,
+            src_access_mask: vk::AccessFlags::SHADER_WRITE,
+            dst_access_mask: vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+            ..Default::default()
+        };
+        unsafe {
+            self.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::DependencyFlags::empty(),
+                &[barrier],
+                &[],
+                &[],
+            );
+        }
+    }
+
+    /// Record commands with `record` into a fresh command buffer, submit it and
+    /// wait for completion.
+    pub fn run<F>(&self, record: F) -> Result<(), VkfftError>
+    where
+        F: FnOnce(vk::CommandBuffer) -> Result<(), VkfftError>,
+    {
+        let allocate_info = vk::CommandBufferAllocateInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_ALLOCATE_INFO,
+            command_pool: self.command_pool,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 1,
+            ..Default::default()
+        };
+        let command_buffer = unsafe { self.device.allocate_command_buffers(&allocate_info)? }[0];
+
+        let result = self.submit_and_wait(command_buffer, record);
+
+        unsafe {
+            self.device
+                .free_command_buffers(self.command_pool, &[command_buffer]);
+        }
+        result
+    }
+
+    fn submit_and_wait<F>(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        record: F,
+    ) -> Result<(), VkfftError>
+    where
+        F: FnOnce(vk::CommandBuffer) -> Result<(), VkfftError>,
+    {
+        let begin_info = vk::CommandBufferBeginInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
+            flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+            ..Default::default()
+        };
+        unsafe {
+            self.device
+                .begin_command_buffer(command_buffer, &begin_info)?
+        };
+        record(command_buffer)?;
+        unsafe { self.device.end_command_buffer(command_buffer)? };
+
+        let submit_info = vk::SubmitInfo {
+            s_type: vk::StructureType::SUBMIT_INFO,
+            command_buffer_count: 1,
+            p_command_buffers: &command_buffer,
+            ..Default::default()
+        };
+        unsafe {
+            self.device
+                .queue_submit(self.queue, &[submit_info], self.fence)?;
+            self.device.wait_for_fences(&[self.fence], true, u64::MAX)?;
+            self.device.reset_fences(&[self.fence])?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DeviceContext {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_fence(self.fence, None);
+            self.device.destroy_command_pool(self.command_pool, None);
+        }
+    }
+}
+
+/// Plan created by [`DeviceContext::create_plan`].
+///
+/// Dereferences to the [`VkFft`] it wraps. The plan records into the
+/// context's command pool and waits on its fence, so it borrows the context.
+pub struct Plan<'ctx> {
+    fft: Box<VkFft>,
+    _ctx: PhantomData<&'ctx DeviceContext>,
+}
+
+impl Deref for Plan<'_> {
+    type Target = VkFft;
+
+    fn deref(&self) -> &VkFft {
+        &self.fft
+    }
+}
+
+impl DerefMut for Plan<'_> {
+    fn deref_mut(&mut self) -> &mut VkFft {
+        &mut self.fft
+    }
+}
+
+/// Storage buffer backed by host-visible, host-coherent memory.
+pub struct HostBuffer<'ctx> {
+    device: ash::Device,
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: vk::DeviceSize,
+    _ctx: PhantomData<&'ctx DeviceContext>,
+}
+
+impl HostBuffer<'_> {
+    pub fn raw(&self) -> vk::Buffer {
+        self.buffer
+    }
+
+    /// Size of the buffer in bytes.
+    pub fn size(&self) -> vk::DeviceSize {
+        self.size
+    }
+
+    /// Map the whole buffer and hand it to `f` as bytes.
+    ///
+    /// # Safety
+    /// No GPU work may access the buffer while it is mapped.
+    pub unsafe fn with_mapped_bytes<R>(
+        &self,
+        f: impl FnOnce(&mut [u8]) -> R,
+    ) -> Result<R, VkfftError> {
+        let ptr = self
+            .device
+            .map_memory(self.memory, 0, self.size, vk::MemoryMapFlags::empty())?;
+        let bytes = core::slice::from_raw_parts_mut(ptr.cast::<u8>(), self.size as usize);
+        let result = f(bytes);
+        self.device.unmap_memory(self.memory);
+        Ok(result)
+    }
+
+    /// Map the whole buffer and hand it to `f` as a slice of `T`.
+    ///
+    /// # Safety
+    /// No GPU work may access the buffer while it is mapped.
+    #[cfg(feature = "typed")]
+    pub unsafe fn with_mapped<T: crate::FftElement, R>(
+        &self,
+        f: impl FnOnce(&mut [T]) -> R,
+    ) -> Result<R, VkfftError> {
+        self.with_mapped_bytes(|bytes| {
+            let len = bytes.len() - bytes.len() % core::mem::size_of::<T>();
+            f(bytemuck::cast_slice_mut(&mut bytes[..len]))
+        })
+    }
+}
+
+impl Drop for HostBuffer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_buffer(self.buffer, None);
+            self.device.free_memory(self.memory, None);
+        }
+    }
+}
+
+/// Storage buffer backed by device-local memory.
+pub struct DeviceBuffer<'ctx> {
+    device: ash::Device,
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: vk::DeviceSize,
+    _ctx: PhantomData<&'ctx DeviceContext>,
+}
+
+impl DeviceBuffer<'_> {
+    pub fn raw(&self) -> vk::Buffer {
+        self.buffer
+    }
+
+    /// Size of the buffer in bytes.
+    pub fn size(&self) -> vk::DeviceSize {
+        self.size
+    }
+}
+
+impl Drop for DeviceBuffer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_buffer(self.buffer, None);
+            self.device.free_memory(self.memory, None);
+        }
+    }
+}
+
+pub(crate) fn find_memory_type_index(
+    properties: &vk::PhysicalDeviceMemoryProperties,
+    type_bits: u32,
+    required: vk::MemoryPropertyFlags,
+) -> Option<u32> {
+    properties.memory_types[..properties.memory_type_count as usize]
+        .iter()
+        .enumerate()
+        .find(|(i, mem_type)| {
+            (type_bits & (1 << i)) != 0 && mem_type.property_flags.contains(required)
+        })
+        .map(|(i, _)| i as u32)
+}
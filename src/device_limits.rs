@@ -0,0 +1,218 @@
+//! Device limits and precision support queried from the physical device.
+//!
+//! VkFFT guesses the subgroup size from the vendor ID and leaves it to the
+//! caller to know whether the device can run fp64 or fp16 kernels; a plan
+//! for an unsupported precision compiles fine and then fails (or misbehaves)
+//! at pipeline creation. [`VkFft::configure_vulkan_with_instance`] reads the
+//! real limits once, passes them to VkFFT and lets `initialize` reject
+//! unsupported precisions up front.
+
+use ash::vk;
+
+use crate::ffi;
+use crate::vkfft::{VkFft, VkFftError};
+
+/// Limits and features of a physical device that matter to VkFFT.
+///
+/// Feature flags report what the physical device supports, not what was
+/// enabled on the logical device; the device must still be created with
+/// them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceLimits {
+    pub vendor_id: u32,
+    /// `maxComputeSharedMemorySize` in bytes.
+    pub max_shared_memory: u64,
+    pub max_workgroup_size: [u64; 3],
+    pub max_workgroup_count: [u64; 3],
+    pub max_workgroup_invocations: u64,
+    /// `maxStorageBufferRange` in bytes, the largest buffer one descriptor
+    /// can address, or 0 if unknown.
+    pub max_storage_buffer_range: u64,
+    /// Subgroup size, or 0 on Vulkan 1.0 devices where it cannot be queried.
+    pub subgroup_size: u64,
+    /// `shaderFloat64`, needed for double precision compute.
+    pub float64: bool,
+    /// `shaderFloat16`, needed for half precision compute.
+    pub float16: bool,
+    /// `storageBuffer16BitAccess`, needed for half precision storage.
+    pub storage16: bool,
+    /// `hostQueryReset`, needed for profiling with `enable_profiling`.
+    pub host_query_reset: bool,
+}
+
+impl DeviceLimits {
+    /// Query `physical_device`.
+    ///
+    /// Subgroup and 16-bit properties need Vulkan 1.1 (`shaderFloat16` and
+    /// `hostQueryReset` 1.2) and are reported as absent on older devices.
+    ///
+    /// # Safety
+    /// `physical_device` must have been enumerated from `instance`, and the
+    /// instance must have been created for Vulkan 1.1 or later if the device
+    /// supports 1.1.
+    pub unsafe fn query(instance: &ash::Instance, physical_device: vk::PhysicalDevice) -> Self {
+        let properties = instance.get_physical_device_properties(physical_device);
+        let limits = &properties.limits;
+        let features = instance.get_physical_device_features(physical_device);
+        let widen = |values: [u32; 3]| values.map(u64::from);
+
+        let mut device_limits = Self {
+            vendor_id: properties.vendor_id,
+            max_shared_memory: limits.max_compute_shared_memory_size.into(),
+            max_workgroup_size: widen(limits.max_compute_work_group_size),
+            max_workgroup_count: widen(limits.max_compute_work_group_count),
+            max_workgroup_invocations: limits.max_compute_work_group_invocations.into(),
+            max_storage_buffer_range: limits.max_storage_buffer_range.into(),
+            subgroup_size: 0,
+            float64: features.shader_float64 == vk::TRUE,
+            float16: false,
+            storage16: false,
+            host_query_reset: false,
+        };
+
+        let api_version = properties.api_version;
+        if api_version >= vk::API_VERSION_1_1 {
+            let mut subgroup = vk::PhysicalDeviceSubgroupProperties::default();
+            let mut properties2 = vk::PhysicalDeviceProperties2::default().push_next(&mut subgroup);
+            instance.get_physical_device_properties2(physical_device, &mut properties2);
+            device_limits.subgroup_size = subgroup.subgroup_size.into();
+
+            let mut storage16 = vk::PhysicalDevice16BitStorageFeatures::default();
+            let mut float16 = vk::PhysicalDeviceShaderFloat16Int8Features::default();
+            let mut host_reset = vk::PhysicalDeviceHostQueryResetFeatures::default();
+            let mut features2 = vk::PhysicalDeviceFeatures2::default().push_next(&mut storage16);
+            if api_version >= vk::API_VERSION_1_2 {
+                features2 = features2.push_next(&mut float16).push_next(&mut host_reset);
+            }
+            instance.get_physical_device_features2(physical_device, &mut features2);
+            device_limits.storage16 = storage16.storage_buffer16_bit_access == vk::TRUE;
+            device_limits.float16 = float16.shader_float16 == vk::TRUE;
+            device_limits.host_query_reset = host_reset.host_query_reset == vk::TRUE;
+        }
+        device_limits
+    }
+}
+
+impl VkFft {
+    /// [`VkFft::configure_vulkan`] plus device limit detection.
+    ///
+    /// Passes the device's shared memory size, workgroup limits and subgroup
+    /// size to VkFFT, and makes `initialize` fail with
+    /// `VKFFT_ERROR_FAILED_TO_CREATE_PIPELINE` before compiling anything if
+    /// the configured precision needs a feature the device lacks.
+    ///
+    /// # Safety
+    /// Same requirements as [`DeviceLimits::query`].
+    pub unsafe fn configure_vulkan_with_instance(
+        &mut self,
+        instance: &ash::Instance,
+        physical_device: vk::PhysicalDevice,
+        device: vk::Device,
+        queue: vk::Queue,
+        command_pool: vk::CommandPool,
+    ) {
+        self.configure_vulkan(physical_device, device, queue, command_pool);
+        let limits = DeviceLimits::query(instance, physical_device);
+        self.set_device_limits(limits);
+    }
+
+    /// Use `limits` instead of querying them, e.g. to plan for a device
+    /// other than the one the plan runs on. Overwrites the shared memory
+    /// size, so call it before [`VkFft::set_tuning`].
+    pub fn set_device_limits(&mut self, limits: DeviceLimits) {
+        self.config.sharedMemorySize = limits.max_shared_memory;
+        self.config.maxComputeWorkGroupSize = limits.max_workgroup_size;
+        self.config.maxComputeWorkGroupCount = limits.max_workgroup_count;
+        if limits.subgroup_size != 0 {
+            self.config.warpSize = limits.subgroup_size;
+        }
+        self.device_limits = Some(limits);
+    }
+
+    /// Limits detected by [`VkFft::configure_vulkan_with_instance`].
+    pub fn device_limits(&self) -> Option<&DeviceLimits> {
+        self.device_limits.as_ref()
+    }
+
+    /// Check the precision flags against the detected device features.
+    ///
+    /// Always succeeds when no limits were detected.
+    pub fn validate_precision(&self) -> Result<(), VkFftError> {
+        let Some(limits) = &self.device_limits else {
+            return Ok(());
+        };
+        let config = &self.config;
+        if config.doublePrecision != 0 && !limits.float64 {
+            return Err(precision_error(
+                "precision: double precision needs shaderFloat64, which the device lacks",
+            ));
+        }
+        if config.halfPrecision != 0 {
+            if !limits.storage16 {
+                return Err(precision_error(
+                    "precision: half precision storage needs storageBuffer16BitAccess, which the device lacks",
+                ));
+            }
+            if config.halfPrecisionMemoryOnly == 0 && !limits.float16 {
+                return Err(precision_error(
+                    "precision: half precision compute needs shaderFloat16, which the device lacks",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn precision_error(message: &'static str) -> VkFftError {
+    VkFftError {
+        code: ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_PIPELINE,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_reach_the_configuration() {
+        let mut fft = VkFft::new();
+        assert!(fft.validate_precision().is_ok());
+
+        fft.set_device_limits(DeviceLimits {
+            max_shared_memory: 48 * 1024,
+            max_workgroup_size: [1024, 1024, 64],
+            max_workgroup_count: [65535; 3],
+            max_workgroup_invocations: 1024,
+            subgroup_size: 32,
+            ..Default::default()
+        });
+        assert_eq!(fft.config.sharedMemorySize, 48 * 1024);
+        assert_eq!(fft.config.maxComputeWorkGroupSize, [1024, 1024, 64]);
+        assert_eq!(fft.config.warpSize, 32);
+        assert_eq!(fft.device_limits().unwrap().max_workgroup_invocations, 1024);
+    }
+
+    #[test]
+    fn rejects_unsupported_precision() {
+        let mut fft = VkFft::new();
+        fft.set_device_limits(DeviceLimits {
+            storage16: true,
+            ..Default::default()
+        });
+        assert!(fft.validate_precision().is_ok());
+
+        fft.set_double_precision(true);
+        let err = fft.validate_precision().unwrap_err();
+        assert!(err.message.contains("shaderFloat64"));
+        fft.set_double_precision(false);
+
+        // fp16 storage computed in fp32 only needs 16-bit storage.
+        fft.config.halfPrecision = 1;
+        fft.config.halfPrecisionMemoryOnly = 1;
+        assert!(fft.validate_precision().is_ok());
+        fft.config.halfPrecisionMemoryOnly = 0;
+        let err = fft.validate_precision().unwrap_err();
+        assert!(err.message.contains("shaderFloat16"));
+    }
+}
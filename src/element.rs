@@ -0,0 +1,257 @@
+//! Element types that can be stored in VkFFT buffers.
+//!
+//! VkFFT selects its kernels from a handful of precision flags. The
+//! [`FftElement`] trait ties those flags to a Rust type so a plan can be
+//! configured from the data it will process, and so host uploads/downloads
+//! can be checked against the element type instead of raw byte counts.
+
+use ash::vk;
+use half::f16;
+use num_complex::Complex;
+
+pub use crate::precision::Precision;
+use crate::vkfft::VkfftError;
+use crate::VkFft;
+
+/// A scalar or complex value that can live in a VkFFT buffer.
+///
+/// # Safety
+/// `STORAGE` must match the scalar type of the element; VkFFT reinterprets the
+/// buffer contents according to it.
+pub unsafe trait FftElement: bytemuck::Pod {
+    /// Precision of each scalar in memory.
+    const STORAGE: Precision;
+    /// Whether the element is an interleaved (re, im) pair.
+    const IS_COMPLEX: bool;
+    /// Precision VkFFT computes in when none is requested explicitly.
+    const DEFAULT_COMPUTE: Precision = Self::STORAGE;
+}
+
+unsafe impl FftElement for f16 {
+    const STORAGE: Precision = Precision::Half;
+    const IS_COMPLEX: bool = false;
+}
+
+unsafe impl FftElement for f32 {
+    const STORAGE: Precision = Precision::Single;
+    const IS_COMPLEX: bool = false;
+}
+
+unsafe impl FftElement for f64 {
+    const STORAGE: Precision = Precision::Double;
+    const IS_COMPLEX: bool = false;
+}
+
+unsafe impl FftElement for Complex<f16> {
+    const STORAGE: Precision = Precision::Half;
+    const IS_COMPLEX: bool = true;
+}
+
+unsafe impl FftElement for Complex<f32> {
+    const STORAGE: Precision = Precision::Single;
+    const IS_COMPLEX: bool = true;
+}
+
+unsafe impl FftElement for Complex<f64> {
+    const STORAGE: Precision = Precision::Double;
+    const IS_COMPLEX: bool = true;
+}
+
+/// VkFFT precision flags for a storage/compute combination.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PrecisionFlags {
+    pub double_precision: bool,
+    pub half_precision: bool,
+    pub half_precision_memory_only: bool,
+    pub double_precision_float_memory: bool,
+}
+
+impl PrecisionFlags {
+    /// Map a storage and compute precision onto VkFFT's flags.
+    ///
+    /// VkFFT supports computing at the storage precision, fp16 storage with
+    /// fp32 math (`halfPrecisionMemoryOnly`) and fp32 storage with fp64 math
+    /// (`doublePrecisionFloatMemory`). Anything else is rejected. fp16
+    /// storage with fp32 math only works out of place through an fp32
+    /// working buffer; see [`VkFft::configure_formatted_buffers`].
+    pub fn new(storage: Precision, compute: Precision) -> Result<Self, &'static str> {
+        let mut flags = Self::default();
+        match (storage, compute) {
+            (Precision::Single, Precision::Single) => {}
+            (Precision::Double, Precision::Double) => flags.double_precision = true,
+            (Precision::Half, Precision::Half) => flags.half_precision = true,
+            (Precision::Half, Precision::Single) => {
+                flags.half_precision = true;
+                flags.half_precision_memory_only = true;
+            }
+            (Precision::Single, Precision::Double) => {
+                flags.double_precision = true;
+                flags.double_precision_float_memory = true;
+            }
+            _ => return Err("unsupported storage/compute precision combination"),
+        }
+        Ok(flags)
+    }
+}
+
+impl VkFft {
+    /// Select precision flags from the element type stored in the buffers.
+    ///
+    /// Computation runs at the element's own precision. Use
+    /// [`VkFft::set_element_type_with_compute`] to compute fp16 data in fp32 or
+    /// fp32 data in fp64.
+    pub fn set_element_type<T: FftElement>(&mut self) {
+        // Storage == compute is always a valid combination.
+        let flags = PrecisionFlags::new(T::STORAGE, T::DEFAULT_COMPUTE).unwrap_or_default();
+        self.set_precision_flags(flags);
+    }
+
+    /// Select precision flags from the element type and a compute precision.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if VkFFT has no kernels for the combination, e.g. fp64
+    /// storage with fp32 computation.
+    pub fn set_element_type_with_compute<T: FftElement>(
+        &mut self,
+        compute: Precision,
+    ) -> Result<(), &'static str> {
+        let flags = PrecisionFlags::new(T::STORAGE, compute)?;
+        self.set_precision_flags(flags);
+        Ok(())
+    }
+
+    /// Write all four precision flags at once.
+    pub fn set_precision_flags(&mut self, flags: PrecisionFlags) {
+        self.config.doublePrecision = flags.double_precision as u64;
+        self.config.halfPrecision = flags.half_precision as u64;
+        self.config.halfPrecisionMemoryOnly = flags.half_precision_memory_only as u64;
+        self.config.doublePrecisionFloatMemory = flags.double_precision_float_memory as u64;
+    }
+}
+
+/// Size in bytes of `count` elements of `T`.
+pub fn buffer_size_of<T: FftElement>(count: usize) -> vk::DeviceSize {
+    (core::mem::size_of::<T>() * count) as vk::DeviceSize
+}
+
+/// Check that `len` bytes at `offset` fit in `memory_size` bytes.
+fn check_range(
+    offset: vk::DeviceSize,
+    len: usize,
+    memory_size: vk::DeviceSize,
+) -> Result<(), VkfftError> {
+    match offset.checked_add(len as vk::DeviceSize) {
+        Some(end) if end <= memory_size => Ok(()),
+        _ => Err(VkfftError::InvalidArgument(
+            "typed copy: range exceeds the memory size",
+        )),
+    }
+}
+
+/// Copy `data` into host-visible, host-coherent `memory` of `memory_size`
+/// bytes, starting at byte `offset`.
+///
+/// # Errors
+/// `VkfftError::InvalidArgument` if `data` does not fit in the memory after
+/// `offset`, or the Vulkan error from mapping it.
+///
+/// # Safety
+/// `memory` must belong to `device`, be host visible and coherent, be
+/// `memory_size` bytes long and not be mapped elsewhere.
+pub unsafe fn upload<T: FftElement>(
+    device: &ash::Device,
+    memory: vk::DeviceMemory,
+    memory_size: vk::DeviceSize,
+    offset: vk::DeviceSize,
+    data: &[T],
+) -> Result<(), VkfftError> {
+    let bytes: &[u8] = bytemuck::cast_slice(data);
+    check_range(offset, bytes.len(), memory_size)?;
+    let ptr = device.map_memory(
+        memory,
+        offset,
+        bytes.len() as vk::DeviceSize,
+        vk::MemoryMapFlags::empty(),
+    )?;
+    core::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.cast::<u8>(), bytes.len());
+    device.unmap_memory(memory);
+    Ok(())
+}
+
+/// Copy `out.len()` elements from host-visible, host-coherent `memory` of
+/// `memory_size` bytes, starting at byte `offset`.
+///
+/// # Errors
+/// As for [`upload`].
+///
+/// # Safety
+/// Same requirements as [`upload`]; in addition no GPU work may be writing
+/// the range while it is read.
+pub unsafe fn download<T: FftElement>(
+    device: &ash::Device,
+    memory: vk::DeviceMemory,
+    memory_size: vk::DeviceSize,
+    offset: vk::DeviceSize,
+    out: &mut [T],
+) -> Result<(), VkfftError> {
+    let bytes: &mut [u8] = bytemuck::cast_slice_mut(out);
+    check_range(offset, bytes.len(), memory_size)?;
+    let ptr = device.map_memory(
+        memory,
+        offset,
+        bytes.len() as vk::DeviceSize,
+        vk::MemoryMapFlags::empty(),
+    )?;
+    core::ptr::copy_nonoverlapping(ptr.cast::<u8>(), bytes.as_mut_ptr(), bytes.len());
+    device.unmap_memory(memory);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_types_select_precision_flags() {
+        let mut fft = VkFft::new();
+
+        fft.set_element_type::<Complex<f64>>();
+        assert_eq!(fft.config.doublePrecision, 1);
+        assert_eq!(fft.config.halfPrecision, 0);
+
+        fft.set_element_type::<Complex<f32>>();
+        assert_eq!(fft.config.doublePrecision, 0);
+
+        fft.set_element_type_with_compute::<f16>(Precision::Single)
+            .unwrap();
+        assert_eq!(fft.config.halfPrecision, 1);
+        assert_eq!(fft.config.halfPrecisionMemoryOnly, 1);
+
+        fft.set_element_type_with_compute::<f32>(Precision::Double)
+            .unwrap();
+        assert_eq!(fft.config.doublePrecision, 1);
+        assert_eq!(fft.config.doublePrecisionFloatMemory, 1);
+        assert_eq!(fft.config.halfPrecision, 0);
+    }
+
+    #[test]
+    fn rejects_lower_compute_precision() {
+        let mut fft = VkFft::new();
+        assert!(fft
+            .set_element_type_with_compute::<Complex<f64>>(Precision::Single)
+            .is_err());
+        assert_eq!(buffer_size_of::<Complex<f32>>(16), 128);
+    }
+
+    #[test]
+    fn typed_copies_must_fit() {
+        assert!(check_range(0, 128, 128).is_ok());
+        assert!(check_range(64, 64, 128).is_ok());
+        assert!(matches!(
+            check_range(64, 128, 128),
+            Err(VkfftError::InvalidArgument(_))
+        ));
+        assert!(check_range(u64::MAX, 1, u64::MAX).is_err());
+    }
+}
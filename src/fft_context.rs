@@ -0,0 +1,343 @@
+//! Self-contained Vulkan setup for running transforms.
+//!
+//! [`DeviceContext`] runs plans on a device the caller created. For tools and
+//! tests that have no Vulkan setup of their own, [`FftContext`] loads the
+//! Vulkan library, creates an instance and a logical device with one compute
+//! queue on a device chosen by [`DeviceSelector`], and tears everything down
+//! on drop. Every precision-related feature the device supports
+//! (`shaderFloat64`, `shaderInt64`, 16-bit storage, `shaderFloat16`) is
+//! enabled.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+use core::ops::Deref;
+
+use ash::vk;
+
+use crate::context::DeviceContext;
+use crate::device_limits::DeviceLimits;
+use crate::vkfft::VkfftError;
+
+/// Which physical device [`FftContext::new`] opens. Only devices with a
+/// compute queue are considered.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum DeviceSelector {
+    /// Discrete GPU, else integrated, virtual, CPU (e.g. lavapipe), in that
+    /// order; ties go to the first enumerated device.
+    #[default]
+    HighPerformance,
+    DiscreteGpu,
+    IntegratedGpu,
+    /// A software implementation such as Mesa's lavapipe.
+    Cpu,
+    /// First device whose name contains the string.
+    Name(String),
+    /// Device at this position in `vkEnumeratePhysicalDevices` order.
+    Index(usize),
+}
+
+impl DeviceSelector {
+    /// Position in `devices` of the device to open.
+    pub fn select(&self, devices: &[DeviceInfo]) -> Option<usize> {
+        let usable = || {
+            devices
+                .iter()
+                .filter(|device| device.compute_queue_family.is_some())
+        };
+        let of_type = |device_type| {
+            usable()
+                .find(|device| device.device_type == device_type)
+                .map(|device| device.index)
+        };
+        match self {
+            Self::HighPerformance => usable()
+                .min_by_key(|device| type_rank(device.device_type))
+                .map(|device| device.index),
+            Self::DiscreteGpu => of_type(vk::PhysicalDeviceType::DISCRETE_GPU),
+            Self::IntegratedGpu => of_type(vk::PhysicalDeviceType::INTEGRATED_GPU),
+            Self::Cpu => of_type(vk::PhysicalDeviceType::CPU),
+            Self::Name(name) => usable()
+                .find(|device| device.name.contains(name.as_str()))
+                .map(|device| device.index),
+            Self::Index(index) => usable()
+                .find(|device| device.index == *index)
+                .map(|device| device.index),
+        }
+    }
+}
+
+fn type_rank(device_type: vk::PhysicalDeviceType) -> u32 {
+    match device_type {
+        vk::PhysicalDeviceType::DISCRETE_GPU => 0,
+        vk::PhysicalDeviceType::INTEGRATED_GPU => 1,
+        vk::PhysicalDeviceType::VIRTUAL_GPU => 2,
+        vk::PhysicalDeviceType::CPU => 3,
+        _ => 4,
+    }
+}
+
+/// A physical device as seen by [`FftContext::devices`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Position in `vkEnumeratePhysicalDevices` order.
+    pub index: usize,
+    pub name: String,
+    pub device_type: vk::PhysicalDeviceType,
+    /// First queue family with compute support, if any.
+    pub compute_queue_family: Option<u32>,
+}
+
+/// Vulkan instance, device and queue owned together with a
+/// [`DeviceContext`].
+///
+/// Dereferences to the [`DeviceContext`], so plans and buffers are created
+/// with [`DeviceContext::create_plan`], [`DeviceContext::create_host_buffer`]
+/// and friends. They borrow the context and so are dropped before the device.
+pub struct FftContext {
+    ctx: Option<DeviceContext>,
+    device: ash::Device,
+    instance: ash::Instance,
+    // The library must stay loaded until the instance is destroyed.
+    _entry: ash::Entry,
+    name: String,
+    limits: DeviceLimits,
+}
+
+impl FftContext {
+    /// Open the device picked by `selector`.
+    ///
+    /// # Errors
+    /// Returns `VkfftError::NoDevice` if no device matches,
+    /// `VK_ERROR_INITIALIZATION_FAILED` if the Vulkan library cannot be
+    /// loaded, and any error from instance or device creation.
+    pub fn new(selector: DeviceSelector) -> Result<Self, VkfftError> {
+        let entry = load_entry()?;
+        let instance = create_instance(&entry)?;
+        match unsafe { open_device(&instance, &selector) } {
+            Ok((ctx, device, name, limits)) => Ok(Self {
+                ctx: Some(ctx),
+                device,
+                instance,
+                _entry: entry,
+                name,
+                limits,
+            }),
+            Err(err) => {
+                unsafe { instance.destroy_instance(None) };
+                Err(err)
+            }
+        }
+    }
+
+    /// List the physical devices visible to a fresh instance.
+    pub fn devices() -> Result<Vec<DeviceInfo>, VkfftError> {
+        let entry = load_entry()?;
+        let instance = create_instance(&entry)?;
+        let devices = unsafe { enumerate(&instance) };
+        unsafe { instance.destroy_instance(None) };
+        Ok(devices?.into_iter().map(|(info, _)| info).collect())
+    }
+
+    /// Name of the opened device.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Limits and features of the opened device. The features listed are
+    /// also enabled on the logical device.
+    pub fn limits(&self) -> &DeviceLimits {
+        &self.limits
+    }
+}
+
+impl Deref for FftContext {
+    type Target = DeviceContext;
+
+    fn deref(&self) -> &DeviceContext {
+        self.ctx.as_ref().unwrap()
+    }
+}
+
+impl Drop for FftContext {
+    fn drop(&mut self) {
+        // Work submitted through the context may still be running, and the
+        // command pool and fence belong to the device.
+        unsafe {
+            let _ = self.device.device_wait_idle();
+        }
+        self.ctx = None;
+        unsafe {
+            self.device.destroy_device(None);
+            self.instance.destroy_instance(None);
+        }
+    }
+}
+
+fn load_entry() -> Result<ash::Entry, VkfftError> {
+    unsafe { ash::Entry::load() }.map_err(|_| vk::Result::ERROR_INITIALIZATION_FAILED.into())
+}
+
+fn create_instance(entry: &ash::Entry) -> Result<ash::Instance, VkfftError> {
+    // Vulkan 1.2 makes shaderFloat16 queryable; older loaders get what they
+    // support.
+    let loader_version =
+        unsafe { entry.try_enumerate_instance_version()? }.unwrap_or(vk::API_VERSION_1_0);
+    let app_info = vk::ApplicationInfo {
+        s_type: vk::StructureType::APPLICATION_INFO,
+        api_version: loader_version.min(vk::API_VERSION_1_2),
+        ..Default::default()
+    };
+    let instance_info = vk::InstanceCreateInfo {
+        s_type: vk::StructureType::INSTANCE_CREATE_INFO,
+        p_application_info: &app_info,
+        ..Default::default()
+    };
+    Ok(unsafe { entry.create_instance(&instance_info, None)? })
+}
+
+unsafe fn enumerate(
+    instance: &ash::Instance,
+) -> Result<Vec<(DeviceInfo, vk::PhysicalDevice)>, VkfftError> {
+    let devices = instance.enumerate_physical_devices()?;
+    Ok(devices
+        .into_iter()
+        .enumerate()
+        .map(|(index, physical_device)| {
+            let properties = instance.get_physical_device_properties(physical_device);
+            let name = CStr::from_ptr(properties.device_name.as_ptr())
+                .to_string_lossy()
+                .into_owned();
+            let compute_queue_family = instance
+                .get_physical_device_queue_family_properties(physical_device)
+                .iter()
+                .position(|family| family.queue_flags.contains(vk::QueueFlags::COMPUTE))
+                .map(|family| family as u32);
+            let info = DeviceInfo {
+                index,
+                name,
+                device_type: properties.device_type,
+                compute_queue_family,
+            };
+            (info, physical_device)
+        })
+        .collect())
+}
+
+unsafe fn open_device(
+    instance: &ash::Instance,
+    selector: &DeviceSelector,
+) -> Result<(DeviceContext, ash::Device, String, DeviceLimits), VkfftError> {
+    let mut devices = enumerate(instance)?;
+    let infos: Vec<DeviceInfo> = devices.iter().map(|(info, _)| info.clone()).collect();
+    let index = selector.select(&infos).ok_or(VkfftError::NoDevice)?;
+    let (info, physical_device) = devices.swap_remove(index);
+    let queue_family_index = info.compute_queue_family.ok_or(VkfftError::NoDevice)?;
+
+    let limits = DeviceLimits::query(instance, physical_device);
+    let supported = instance.get_physical_device_features(physical_device);
+    let enabled_features = vk::PhysicalDeviceFeatures {
+        shader_float64: supported.shader_float64,
+        shader_int64: supported.shader_int64,
+        ..Default::default()
+    };
+    let mut storage16 = vk::PhysicalDevice16BitStorageFeatures {
+        storage_buffer16_bit_access: limits.storage16.into(),
+        ..Default::default()
+    };
+    let mut float16 = vk::PhysicalDeviceShaderFloat16Int8Features {
+        shader_float16: limits.float16.into(),
+        ..Default::default()
+    };
+    let mut host_reset = vk::PhysicalDeviceHostQueryResetFeatures {
+        host_query_reset: limits.host_query_reset.into(),
+        ..Default::default()
+    };
+
+    let priorities = [1.0_f32];
+    let queue_info = vk::DeviceQueueCreateInfo::default()
+        .queue_family_index(queue_family_index)
+        .queue_priorities(&priorities);
+    let mut device_info = vk::DeviceCreateInfo::default()
+        .queue_create_infos(core::slice::from_ref(&queue_info))
+        .enabled_features(&enabled_features);
+    // Chain the 1.1/1.2 feature structs only where they were queryable.
+    if limits.storage16 {
+        device_info = device_info.push_next(&mut storage16);
+    }
+    if limits.float16 {
+        device_info = device_info.push_next(&mut float16);
+    }
+    // Lets plans created on this context enable profiling.
+    if limits.host_query_reset {
+        device_info = device_info.push_next(&mut host_reset);
+    }
+    let device = instance.create_device(physical_device, &device_info, None)?;
+    let queue = device.get_device_queue(queue_family_index, 0);
+
+    match DeviceContext::new(
+        instance.clone(),
+        device.clone(),
+        physical_device,
+        queue,
+        queue_family_index,
+    ) {
+        Ok(ctx) => Ok((ctx, device, info.name, limits)),
+        Err(err) => {
+            device.destroy_device(None);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn device(index: usize, name: &str, device_type: vk::PhysicalDeviceType) -> DeviceInfo {
+        DeviceInfo {
+            index,
+            name: name.to_string(),
+            device_type,
+            compute_queue_family: Some(0),
+        }
+    }
+
+    #[test]
+    fn selects_devices_by_preference() {
+        let mut devices = vec![
+            device(
+                0,
+                "llvmpipe (LLVM 17.0.6, 256 bits)",
+                vk::PhysicalDeviceType::CPU,
+            ),
+            device(
+                1,
+                "Intel(R) UHD Graphics 770",
+                vk::PhysicalDeviceType::INTEGRATED_GPU,
+            ),
+            device(
+                2,
+                "NVIDIA GeForce RTX 4090",
+                vk::PhysicalDeviceType::DISCRETE_GPU,
+            ),
+        ];
+        assert_eq!(DeviceSelector::HighPerformance.select(&devices), Some(2));
+        assert_eq!(DeviceSelector::IntegratedGpu.select(&devices), Some(1));
+        assert_eq!(DeviceSelector::Cpu.select(&devices), Some(0));
+        assert_eq!(
+            DeviceSelector::Name("RTX".to_string()).select(&devices),
+            Some(2)
+        );
+        assert_eq!(DeviceSelector::Index(1).select(&devices), Some(1));
+        assert_eq!(DeviceSelector::Index(3).select(&devices), None);
+
+        // Devices without a compute queue are never picked.
+        devices[2].compute_queue_family = None;
+        assert_eq!(DeviceSelector::HighPerformance.select(&devices), Some(1));
+        assert_eq!(DeviceSelector::DiscreteGpu.select(&devices), None);
+        assert_eq!(DeviceSelector::Index(2).select(&devices), None);
+    }
+}
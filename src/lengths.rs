@@ -0,0 +1,244 @@
+//! CPU-side queries about transform lengths.
+//!
+//! VkFFT has radix kernels for the primes 2 to 13. Lengths with a larger
+//! prime factor `p` go through Rader's algorithm when `p - 1` splits into
+//! those radices and the sequence fits in shared memory, and through
+//! Bluestein's algorithm (a padded convolution) otherwise. The functions here
+//! reproduce that choice without a device so padded sizes can be picked
+//! before a plan is built.
+//!
+//! The thresholds mirror VkFFT's defaults; a device with a different shared
+//! memory size or a plan with tuned Rader limits can make a different
+//! choice. [`VkFft::plan_info`](crate::VkFft::plan_info) reports what an
+//! initialized plan actually uses.
+
+use crate::ffi;
+use crate::precision::Precision;
+use crate::radix::{is_smooth, RADICES};
+use crate::result::VkFftError;
+pub use crate::transform::TransformKind;
+
+/// Largest length VkFFT accepts along one axis.
+pub const MAX_LENGTH: u64 = 1 << 32;
+
+/// Shared memory assumed by [`query_length`], in bytes.
+pub const DEFAULT_SHARED_MEMORY: u64 = 32 * 1024;
+
+/// Padded Bluestein lengths up to this size are powers of two; longer ones
+/// may use primes up to 7 (VkFFT's default `fixMaxRadixBluestein`).
+const BLUESTEIN_POW2_LIMIT: u64 = 1 << 20;
+
+/// Algorithm VkFFT uses for an axis.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Algorithm {
+    /// Every prime factor has a radix kernel.
+    Radix,
+    /// At least one prime factor is handled by Rader's algorithm.
+    Rader,
+    /// The axis is computed as a convolution of `padded` length.
+    Bluestein { padded: u64 },
+}
+
+/// Result of [`query_length`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LengthInfo {
+    /// Length of the complex sequence VkFFT actually transforms, e.g. `n / 2`
+    /// for an even-length R2C.
+    pub effective_length: u64,
+    pub algorithm: Algorithm,
+}
+
+/// Predict how VkFFT transforms `length` points.
+///
+/// `precision` is the precision VkFFT computes in, which sets how many values
+/// fit in shared memory: fp16 storage computed in fp32
+/// (`halfPrecisionMemoryOnly`) is `Single`.
+///
+/// # Errors
+///
+/// Returns the error VkFFT's `initialize` would report for an unsupported
+/// length (`VKFFT_ERROR_EMPTY_size`, `VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH` or
+/// its R2C/R2R variants).
+pub fn query_length(
+    length: u64,
+    precision: Precision,
+    kind: TransformKind,
+) -> Result<LengthInfo, VkFftError> {
+    query_length_with_shared_memory(length, precision, kind, DEFAULT_SHARED_MEMORY)
+}
+
+/// [`query_length`] for a device with `shared_memory` bytes per workgroup.
+pub fn query_length_with_shared_memory(
+    length: u64,
+    precision: Precision,
+    kind: TransformKind,
+    shared_memory: u64,
+) -> Result<LengthInfo, VkFftError> {
+    if length == 0 {
+        return Err(VkFftError::from_result(
+            ffi::VkFFTResult::VKFFT_ERROR_EMPTY_size,
+        ));
+    }
+    let unsupported = match kind {
+        TransformKind::C2c => ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH,
+        TransformKind::R2c => ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH_R2C,
+        _ => ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH_R2R,
+    };
+    let effective_length = match effective_length(length, kind) {
+        Some(n) if n <= MAX_LENGTH => n,
+        _ => return Err(VkFftError::from_result(unsupported)),
+    };
+
+    let complex_bytes = match precision {
+        Precision::Half => 4,
+        Precision::Single => 8,
+        Precision::Double => 16,
+    };
+    let algorithm = algorithm_for(effective_length, shared_memory / complex_bytes);
+    if let Algorithm::Bluestein { padded } = algorithm {
+        if padded > MAX_LENGTH {
+            return Err(VkFftError::from_result(unsupported));
+        }
+    }
+    Ok(LengthInfo {
+        effective_length,
+        algorithm,
+    })
+}
+
+/// Smallest length `>= length` that VkFFT handles with radix kernels only.
+///
+/// Returns `None` if there is no such length within [`MAX_LENGTH`].
+pub fn nearest_fast_size(length: u64, kind: TransformKind) -> Option<u64> {
+    let min = match kind {
+        TransformKind::Dct1 => 2,
+        _ => 1,
+    };
+    (length.max(min)..=MAX_LENGTH)
+        .find(|&n| effective_length(n, kind).is_some_and(|effective| is_smooth(effective, 13)))
+}
+
+/// Length of the complex FFT VkFFT runs for `length` points of `kind`.
+fn effective_length(length: u64, kind: TransformKind) -> Option<u64> {
+    let halved = |n: u64| if n.is_multiple_of(2) { n / 2 } else { n };
+    match kind {
+        TransformKind::C2c => Some(length),
+        TransformKind::R2c | TransformKind::Dct2 | TransformKind::Dct3 | TransformKind::Dct4 => {
+            Some(halved(length))
+        }
+        // DCT-I is an R2C of 2(N - 1) real points.
+        TransformKind::Dct1 => (length >= 2).then(|| length - 1),
+    }
+}
+
+fn algorithm_for(n: u64, rader_limit: u64) -> Algorithm {
+    let mut algorithm = Algorithm::Radix;
+    for p in prime_factors(n) {
+        if RADICES.contains(&p) {
+            continue;
+        }
+        if p <= rader_limit && is_smooth(p - 1, 13) {
+            algorithm = Algorithm::Rader;
+        } else {
+            return Algorithm::Bluestein {
+                padded: bluestein_padding(n),
+            };
+        }
+    }
+    algorithm
+}
+
+/// Padded length VkFFT picks for a `2n - 1` point convolution.
+fn bluestein_padding(n: u64) -> u64 {
+    let mut padded = 2 * n - 1;
+    let max_prime = if padded <= BLUESTEIN_POW2_LIMIT { 2 } else { 7 };
+    while !is_smooth(padded, max_prime) {
+        padded += 1;
+    }
+    padded
+}
+
+/// Distinct prime factors of `n` in increasing order.
+fn prime_factors(mut n: u64) -> impl Iterator<Item = u64> {
+    let mut p = 2;
+    core::iter::from_fn(move || {
+        while n > 1 {
+            if p * p > n {
+                let last = n;
+                n = 1;
+                return Some(last);
+            }
+            if n.is_multiple_of(p) {
+                while n.is_multiple_of(p) {
+                    n /= p;
+                }
+                return Some(p);
+            }
+            p += 1;
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_algorithms() {
+        let c2c = |n| {
+            query_length(n, Precision::Single, TransformKind::C2c)
+                .unwrap()
+                .algorithm
+        };
+        assert_eq!(c2c(1024), Algorithm::Radix);
+        assert_eq!(c2c(2 * 3 * 5 * 7 * 11 * 13), Algorithm::Radix);
+        // 17 - 1 = 2^4, 4099 - 1 = 2 * 3 * 683.
+        assert_eq!(c2c(17 * 8), Algorithm::Rader);
+        assert_eq!(c2c(4099), Algorithm::Bluestein { padded: 16384 });
+        // 8191 - 1 = 2 * 3^2 * 5 * 7 * 13, but it does not fit in 32 KiB.
+        assert!(matches!(c2c(8191), Algorithm::Bluestein { .. }));
+        // fp16 values take half the space.
+        let half = query_length(8191, Precision::Half, TransformKind::C2c).unwrap();
+        assert_eq!(half.algorithm, Algorithm::Rader);
+    }
+
+    #[test]
+    fn maps_real_transforms_to_complex_lengths() {
+        let r2c = query_length(2 * 17, Precision::Double, TransformKind::R2c).unwrap();
+        assert_eq!(r2c.effective_length, 17);
+        assert_eq!(r2c.algorithm, Algorithm::Rader);
+
+        let dct1 = query_length(65, Precision::Single, TransformKind::Dct1).unwrap();
+        assert_eq!(dct1.effective_length, 64);
+        assert_eq!(dct1.algorithm, Algorithm::Radix);
+    }
+
+    #[test]
+    fn rejects_unsupported_lengths() {
+        let err = query_length(0, Precision::Single, TransformKind::C2c).unwrap_err();
+        assert_eq!(err.code, ffi::VkFFTResult::VKFFT_ERROR_EMPTY_size);
+
+        let err = query_length(MAX_LENGTH + 1, Precision::Single, TransformKind::C2c).unwrap_err();
+        assert_eq!(
+            err.code,
+            ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH
+        );
+
+        let err = query_length(1, Precision::Single, TransformKind::Dct1).unwrap_err();
+        assert_eq!(
+            err.code,
+            ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH_R2R
+        );
+    }
+
+    #[test]
+    fn finds_nearest_fast_size() {
+        assert_eq!(nearest_fast_size(1000, TransformKind::C2c), Some(1000));
+        assert_eq!(nearest_fast_size(1021, TransformKind::C2c), Some(1024));
+        assert_eq!(nearest_fast_size(17, TransformKind::C2c), Some(18));
+        // 34 / 2 = 17 is not smooth; odd lengths run as a full C2C.
+        assert_eq!(nearest_fast_size(34, TransformKind::R2c), Some(35));
+        assert_eq!(nearest_fast_size(0, TransformKind::Dct1), Some(2));
+    }
+}
@@ -1,6 +1,9 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
 
 pub mod ffi {
     #![allow(non_camel_case_types)]
@@ -11,8 +14,80 @@ pub mod ffi {
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 }
 
+pub mod result;
+pub mod version;
+pub use version::{version, Version, VKFFT_COMMIT};
+
 // Optional higher-level wrapper goes behind a feature once stabilized for your VkFFT version.
 #[cfg(feature = "wrapper")]
 pub mod vkfft;
 #[cfg(feature = "wrapper")]
 pub use vkfft::VkFft;
+#[cfg(feature = "wrapper")]
+pub mod normalization;
+#[cfg(feature = "wrapper")]
+pub use normalization::{Normalization, Normalizer};
+#[cfg(feature = "wrapper")]
+pub mod bluestein;
+#[cfg(feature = "alloc")]
+mod radix;
+#[cfg(feature = "wrapper")]
+mod trace;
+#[cfg(feature = "wrapper")]
+pub use bluestein::BluesteinPadding;
+#[cfg(feature = "wrapper")]
+pub mod profiling;
+#[cfg(feature = "wrapper")]
+pub use profiling::FftTiming;
+#[cfg(feature = "wrapper")]
+pub mod plan_info;
+#[cfg(feature = "wrapper")]
+pub use plan_info::PlanInfo;
+#[cfg(feature = "wrapper")]
+pub mod plan_bundle;
+#[cfg(feature = "wrapper")]
+pub mod serialized_plan;
+#[cfg(feature = "wrapper")]
+pub use plan_bundle::PlanBundle;
+#[cfg(feature = "wrapper")]
+pub mod device_limits;
+#[cfg(feature = "wrapper")]
+pub use device_limits::DeviceLimits;
+#[cfg(feature = "wrapper")]
+pub mod tuning;
+#[cfg(feature = "wrapper")]
+pub use tuning::TuningOptions;
+#[cfg(feature = "wrapper")]
+pub mod context;
+#[cfg(feature = "wrapper")]
+pub use context::DeviceContext;
+#[cfg(all(feature = "wrapper", feature = "std"))]
+pub mod fft_context;
+#[cfg(all(feature = "wrapper", feature = "std"))]
+pub use fft_context::{DeviceSelector, FftContext};
+#[cfg(feature = "wrapper")]
+pub mod autotune;
+#[cfg(feature = "wrapper")]
+pub use autotune::{AutoTuner, TuningCache};
+#[cfg(feature = "typed")]
+pub mod element;
+#[cfg(feature = "typed")]
+pub use element::FftElement;
+#[cfg(feature = "alloc")]
+pub mod lengths;
+#[cfg(feature = "alloc")]
+pub mod precision;
+#[cfg(feature = "alloc")]
+pub use precision::Precision;
+#[cfg(feature = "alloc")]
+pub mod transform;
+#[cfg(feature = "alloc")]
+pub use transform::TransformKind;
+#[cfg(feature = "typed")]
+pub mod plan_description;
+#[cfg(feature = "typed")]
+pub use plan_description::PlanDescription;
+#[cfg(feature = "ndarray")]
+pub mod ndarray_fft;
+#[cfg(feature = "wgpu")]
+pub mod wgpu_interop;
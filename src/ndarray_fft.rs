@@ -0,0 +1,329 @@
+//! FFTs over `ndarray` arrays.
+//!
+//! VkFFT numbers its axes from the fastest-varying one: `size[0]` is
+//! contiguous in memory and `bufferStride[i]` is the element stride of axis
+//! `i + 1`. `ndarray` numbers axes from the outermost one. [`ArrayLayout`]
+//! maps between the two. When the array's strides can be expressed directly
+//! (unit innermost stride, positive, non-overlapping) the device buffer uses
+//! the same layout as host memory, padding included; otherwise the data is
+//! packed into C order on upload.
+//!
+//! Axes that are not transformed are skipped with `omitDimension`.
+
+use alloc::vec::Vec;
+use ndarray::{ArrayBase, DataMut, Dimension, IntoDimension};
+
+use crate::context::DeviceContext;
+use crate::element::{buffer_size_of, FftElement};
+use crate::ffi;
+use crate::normalization::{Normalization, Normalizer};
+use crate::vkfft::VkfftError;
+
+/// Maximum number of axes a VkFFT plan can describe.
+pub const MAX_AXES: usize = 4;
+
+/// Device buffer layout for an array, in VkFFT axis order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArrayLayout {
+    /// Number of VkFFT dimensions (equal to the array's `ndim`).
+    pub dims: usize,
+    /// `size[]` in VkFFT order.
+    pub sizes: [u64; MAX_AXES],
+    /// `bufferStride[]` in elements.
+    pub buffer_stride: [u64; MAX_AXES],
+    /// `omitDimension[]`: true for axes that are not transformed.
+    pub omit: [bool; MAX_AXES],
+    /// For each VkFFT dimension, the `ndarray` axis it came from.
+    pub array_axis: [usize; MAX_AXES],
+    /// Device element stride of each `ndarray` axis.
+    pub strides: Vec<usize>,
+    /// Number of elements the device buffer must hold.
+    pub span: usize,
+    /// Whether the device buffer mirrors host memory (`false` means packed).
+    pub native: bool,
+}
+
+impl ArrayLayout {
+    /// Compute the layout for an array with `shape` and element `strides`
+    /// (as returned by `ArrayBase::strides`) transformed along `axes`.
+    pub fn new(shape: &[usize], strides: &[isize], axes: &[usize]) -> Result<Self, VkfftError> {
+        let ndim = shape.len();
+        if ndim == 0 || shape.contains(&0) {
+            return Err(ffi::VkFFTResult::VKFFT_ERROR_EMPTY_size.into());
+        }
+        if ndim > MAX_AXES {
+            return Err(ffi::VkFFTResult::VKFFT_ERROR_FFTdim_GT_MAX_FFT_DIMENSIONS.into());
+        }
+        if axes.is_empty() {
+            return Err(VkfftError::InvalidArgument(
+                "fft_axes: no axes to transform",
+            ));
+        }
+        if axes.iter().any(|&axis| axis >= ndim) {
+            return Err(VkfftError::InvalidArgument("fft_axes: axis out of range"));
+        }
+
+        let (order, device_strides, native) = match native_order(shape, strides) {
+            Some(order) => {
+                let strides = strides.iter().map(|&s| s as usize).collect();
+                (order, strides, true)
+            }
+            None => {
+                // Packed C order: the last ndarray axis is contiguous.
+                let mut order = [0usize; MAX_AXES];
+                for (i, slot) in order.iter_mut().take(ndim).enumerate() {
+                    *slot = ndim - 1 - i;
+                }
+                let mut packed = alloc::vec![0usize; ndim];
+                let mut stride = 1;
+                for axis in (0..ndim).rev() {
+                    packed[axis] = stride;
+                    stride *= shape[axis];
+                }
+                (order, packed, false)
+            }
+        };
+
+        let span = 1
+            + (0..ndim)
+                .map(|axis| (shape[axis] - 1) * device_strides[axis])
+                .sum::<usize>();
+
+        let mut sizes = [1u64; MAX_AXES];
+        let mut buffer_stride = [span as u64; MAX_AXES];
+        let mut omit = [false; MAX_AXES];
+        for i in 0..ndim {
+            let axis = order[i];
+            sizes[i] = shape[axis] as u64;
+            omit[i] = !axes.contains(&axis);
+            if i + 1 < ndim {
+                buffer_stride[i] = device_strides[order[i + 1]] as u64;
+            }
+        }
+
+        Ok(Self {
+            dims: ndim,
+            sizes,
+            buffer_stride,
+            omit,
+            array_axis: order,
+            strides: device_strides,
+            span,
+            native,
+        })
+    }
+
+    /// Device element offset of the element at `index`.
+    pub fn offset(&self, index: &[usize]) -> usize {
+        index
+            .iter()
+            .zip(&self.strides)
+            .map(|(&i, &stride)| i * stride)
+            .sum()
+    }
+}
+
+/// Order axes by stride if the array's own layout is usable by VkFFT.
+fn native_order(shape: &[usize], strides: &[isize]) -> Option<[usize; MAX_AXES]> {
+    let ndim = shape.len();
+    if strides.iter().any(|&s| s < 0) {
+        return None;
+    }
+
+    // Length-1 axes carry arbitrary strides; sort them to the outside.
+    let key = |axis: usize| {
+        if shape[axis] == 1 {
+            (1, 0)
+        } else {
+            (0, strides[axis] as usize)
+        }
+    };
+    let mut order = [0usize; MAX_AXES];
+    for (i, slot) in order.iter_mut().take(ndim).enumerate() {
+        *slot = i;
+    }
+    // Stable sort keeps ndarray's reverse order for equal strides.
+    order[..ndim].reverse();
+    order[..ndim].sort_by_key(|&axis| key(axis));
+
+    if shape[order[0]] > 1 && strides[order[0]] != 1 {
+        return None;
+    }
+    let mut extent = 1usize;
+    for &axis in &order[..ndim] {
+        if shape[axis] == 1 {
+            continue;
+        }
+        let stride = strides[axis] as usize;
+        if stride < extent {
+            return None;
+        }
+        extent = stride * shape[axis];
+    }
+    Some(order)
+}
+
+impl DeviceContext {
+    /// Forward FFT of `array` along `axes`, in place.
+    ///
+    /// The array is uploaded to a host-visible buffer, transformed and read
+    /// back. Elements must be complex. Scaling follows NumPy's default
+    /// (`norm="backward"`): the forward transform is unscaled.
+    pub fn fft_axes<A, S, D>(
+        &self,
+        array: &mut ArrayBase<S, D>,
+        axes: &[usize],
+    ) -> Result<(), VkfftError>
+    where
+        A: FftElement,
+        S: DataMut<Elem = A>,
+        D: Dimension,
+    {
+        self.transform_axes(array, axes, -1, Normalization::Backward)
+    }
+
+    /// Inverse FFT of `array` along `axes`, in place, scaled by `1/N`.
+    pub fn ifft_axes<A, S, D>(
+        &self,
+        array: &mut ArrayBase<S, D>,
+        axes: &[usize],
+    ) -> Result<(), VkfftError>
+    where
+        A: FftElement,
+        S: DataMut<Elem = A>,
+        D: Dimension,
+    {
+        self.transform_axes(array, axes, 1, Normalization::Backward)
+    }
+
+    /// Forward FFT of `array` along `axes` with an explicit normalization.
+    pub fn fft_axes_norm<A, S, D>(
+        &self,
+        array: &mut ArrayBase<S, D>,
+        axes: &[usize],
+        norm: Normalization,
+    ) -> Result<(), VkfftError>
+    where
+        A: FftElement,
+        S: DataMut<Elem = A>,
+        D: Dimension,
+    {
+        self.transform_axes(array, axes, -1, norm)
+    }
+
+    /// Inverse FFT of `array` along `axes` with an explicit normalization.
+    pub fn ifft_axes_norm<A, S, D>(
+        &self,
+        array: &mut ArrayBase<S, D>,
+        axes: &[usize],
+        norm: Normalization,
+    ) -> Result<(), VkfftError>
+    where
+        A: FftElement,
+        S: DataMut<Elem = A>,
+        D: Dimension,
+    {
+        self.transform_axes(array, axes, 1, norm)
+    }
+
+    fn transform_axes<A, S, D>(
+        &self,
+        array: &mut ArrayBase<S, D>,
+        axes: &[usize],
+        direction: i32,
+        norm: Normalization,
+    ) -> Result<(), VkfftError>
+    where
+        A: FftElement,
+        S: DataMut<Elem = A>,
+        D: Dimension,
+    {
+        if !A::IS_COMPLEX {
+            return Err(VkfftError::InvalidArgument(
+                "fft_axes: element type must be complex",
+            ));
+        }
+        let layout = ArrayLayout::new(array.shape(), array.strides(), axes)?;
+        let size = buffer_size_of::<A>(layout.span);
+        let buffer = self.create_host_buffer(size)?;
+
+        unsafe {
+            buffer.with_mapped::<A, _>(|device| {
+                for (index, value) in array.indexed_iter() {
+                    device[layout.offset(index.into_dimension().slice())] = *value;
+                }
+            })?;
+        }
+
+        let mut plan = self.create_plan();
+        plan.configure_dimensions(&layout.sizes[..layout.dims]);
+        plan.set_element_type::<A>();
+        plan.set_strides(layout.buffer_stride, None, None);
+        for (axis, omit) in layout.omit.iter().enumerate().take(layout.dims) {
+            plan.set_axis_transform(axis, !omit)
+                .map_err(VkfftError::Unsupported)?;
+        }
+        plan.configure_buffers(&[buffer.raw()], None)?;
+        plan.set_buffer_sizes(&[size]);
+        plan.set_normalization(norm);
+        plan.initialize()?;
+
+        if plan.scale_factor(direction).is_some() {
+            let mut normalizer = unsafe { Normalizer::new(self.device())? };
+            self.run(|command_buffer| unsafe {
+                plan.append_normalized(command_buffer, direction, &mut normalizer)
+            })?;
+        } else {
+            self.run(|command_buffer| plan.append(command_buffer, direction))?;
+        }
+
+        unsafe {
+            buffer.with_mapped::<A, _>(|device| {
+                for (index, value) in array.indexed_iter_mut() {
+                    *value = device[layout.offset(index.into_dimension().slice())];
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_c_order_to_vkfft_order() {
+        // Array3 of shape (4, 8, 16) in standard layout.
+        let layout = ArrayLayout::new(&[4, 8, 16], &[128, 16, 1], &[0, 2]).unwrap();
+        assert!(layout.native);
+        assert_eq!(&layout.sizes[..3], &[16, 8, 4]);
+        assert_eq!(&layout.buffer_stride[..3], &[16, 128, 512]);
+        assert_eq!(&layout.omit[..3], &[false, true, false]);
+        assert_eq!(layout.span, 512);
+    }
+
+    #[test]
+    fn keeps_padded_rows_without_packing() {
+        // 8x10 view into an 8x16 allocation.
+        let layout = ArrayLayout::new(&[8, 10], &[16, 1], &[1]).unwrap();
+        assert!(layout.native);
+        assert_eq!(&layout.sizes[..2], &[10, 8]);
+        assert_eq!(layout.buffer_stride[0], 16);
+        assert_eq!(layout.span, 7 * 16 + 10);
+        assert_eq!(&layout.omit[..2], &[false, true]);
+    }
+
+    #[test]
+    fn packs_layouts_vkfft_cannot_address() {
+        // Fortran order is fine, a strided innermost axis is not.
+        let fortran = ArrayLayout::new(&[4, 8], &[1, 4], &[0, 1]).unwrap();
+        assert!(fortran.native);
+        assert_eq!(&fortran.array_axis[..2], &[0, 1]);
+
+        let strided = ArrayLayout::new(&[4, 8], &[16, 2], &[0]).unwrap();
+        assert!(!strided.native);
+        assert_eq!(strided.strides, [8, 1]);
+        assert_eq!(strided.offset(&[1, 3]), 11);
+    }
+}
@@ -0,0 +1,581 @@
+//! NumPy-style normalization modes.
+//!
+//! VkFFT can only scale the inverse transform by `1/N` (`normalize = 1`). The
+//! other modes NumPy offers are built from that flag plus a small compute
+//! shader that multiplies the output buffer by a constant. The scaling pass is
+//! recorded into the same command buffer right after the FFT dispatches, so no
+//! extra submission or host round trip is involved.
+//!
+//! The shaders are precompiled to SPIR-V from the GLSL sources in
+//! `src/shaders/`.
+
+use alloc::vec::Vec;
+use ash::vk;
+
+use crate::vkfft::{VkFft, VkfftError};
+
+static SCALE_F16_SPV: &[u8] = include_bytes!("shaders/scale_f16.spv");
+static SCALE_F32_SPV: &[u8] = include_bytes!("shaders/scale_f32.spv");
+static SCALE_F64_SPV: &[u8] = include_bytes!("shaders/scale_f64.spv");
+
+const WORKGROUP_SIZE: u64 = 256;
+const MAX_WORKGROUPS: u64 = 65535;
+const MAX_DESCRIPTOR_SETS: u32 = 64;
+
+/// Scaling convention, matching the `norm` argument of `numpy.fft`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Normalization {
+    /// No scaling in either direction (VkFFT's default).
+    #[default]
+    None,
+    /// Inverse scaled by `1/N` (NumPy's default, `norm="backward"`).
+    Backward,
+    /// Both directions scaled by `1/sqrt(N)` (`norm="ortho"`).
+    Ortho,
+    /// Forward scaled by `1/N` (`norm="forward"`).
+    Forward,
+}
+
+impl Normalization {
+    /// Whether VkFFT's own `normalize` flag implements this mode's inverse.
+    pub(crate) fn uses_vkfft_normalize(self) -> bool {
+        self == Self::Backward
+    }
+
+    /// Extra factor to apply after a transform of `n` points, if any.
+    ///
+    /// `inverse` follows VkFFT: `true` for the `1` direction.
+    pub fn extra_scale(self, n: u64, inverse: bool) -> Option<f64> {
+        match (self, inverse) {
+            (Self::Ortho, _) => Some(1.0 / sqrt(n as f64)),
+            (Self::Forward, false) => Some(1.0 / n as f64),
+            _ => None,
+        }
+    }
+}
+
+// `f64::sqrt` lives in std; this keeps the module usable from `no_std`.
+fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    // Newton iterations from a bit-level initial guess converge to full
+    // precision for the transform sizes VkFFT supports.
+    let mut y = f64::from_bits((x.to_bits() >> 1) + (1023u64 << 51));
+    for _ in 0..6 {
+        y = 0.5 * (y + x / y);
+    }
+    y
+}
+
+impl VkFft {
+    /// Select the scaling convention.
+    ///
+    /// `Backward` maps directly to VkFFT's `normalize` flag. `Ortho` and
+    /// `Forward` need a scaling pass; record transforms with
+    /// [`VkFft::append_normalized`], as [`VkFft::append`] rejects directions
+    /// that need one.
+    pub fn set_normalization(&mut self, normalization: Normalization) {
+        self.normalization = normalization;
+        self.config.normalize = normalization.uses_vkfft_normalize() as u64;
+    }
+
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// Number of points in one transform, over the axes that are not omitted.
+    pub fn transform_length(&self) -> u64 {
+        let dims = (self.config.FFTdim as usize).min(self.config.size.len());
+        (0..dims)
+            .filter(|&axis| self.config.omitDimension[axis] == 0)
+            .map(|axis| self.config.size[axis])
+            .product()
+    }
+
+    /// Factor the scaling pass must apply after a transform in `direction`.
+    pub fn scale_factor(&self, direction: i32) -> Option<f64> {
+        self.normalization
+            .extra_scale(self.transform_length(), direction == 1)
+    }
+
+    /// Record the transform followed by the scaling pass the current
+    /// [`Normalization`] needs.
+    ///
+    /// The output buffers (or the in-place buffers) are scaled in full, so
+    /// their sizes must be known through [`VkFft::set_output_buffer_sizes`]
+    /// (or [`VkFft::set_buffer_sizes`]).
+    ///
+    /// # Safety
+    /// `normalizer` must have been created on the plan's device, and
+    /// `command_buffer` must be in the recording state.
+    pub unsafe fn append_normalized(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        direction: i32,
+        normalizer: &mut Normalizer,
+    ) -> Result<(), VkfftError> {
+        self.append_unscaled(command_buffer, direction)?;
+
+        let Some(factor) = self.scale_factor(direction) else {
+            return Ok(());
+        };
+        let targets = self.scale_targets()?;
+        let storage = ScalarStorage::of(self);
+        normalizer.record_barrier(command_buffer);
+        for (buffer, size) in targets {
+            normalizer.record_scale(command_buffer, buffer, size, storage, factor)?;
+        }
+        normalizer.record_barrier(command_buffer);
+        Ok(())
+    }
+
+    /// The buffers the scaling pass writes with their sizes in bytes: the
+    /// output buffers of out-of-place plans, the in-place buffers otherwise.
+    fn scale_targets(&self) -> Result<Vec<(vk::Buffer, u64)>, VkfftError> {
+        let (buffers, sizes) = if self.output_buffers.is_empty() {
+            (&self.buffers, &self.buffer_sizes)
+        } else {
+            (&self.output_buffers, &self.output_buffer_sizes)
+        };
+        if sizes.len() != buffers.len() {
+            return Err(VkfftError::InvalidArgument(
+                "append_normalized: the sizes of the scaled buffers must be configured",
+            ));
+        }
+        Ok(buffers.iter().copied().zip(sizes.iter().copied()).collect())
+    }
+}
+
+/// Scalar format of the data in a plan's buffers.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum ScalarStorage {
+    Half,
+    Single,
+    Double,
+}
+
+impl ScalarStorage {
+    fn of(fft: &VkFft) -> Self {
+        let config = &fft.config;
+        if config.halfPrecision != 0 {
+            Self::Half
+        } else if config.doublePrecision != 0 && config.doublePrecisionFloatMemory == 0 {
+            Self::Double
+        } else {
+            Self::Single
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::Half => 0,
+            Self::Single => 1,
+            Self::Double => 2,
+        }
+    }
+}
+
+/// Pipelines and descriptor sets for the scaling pass.
+///
+/// One normalizer can serve any number of plans on the same device. Pipelines
+/// are created on first use per precision; descriptor sets are cached per
+/// buffer handle and size. When the descriptor pool is full the cache starts
+/// over in a fresh pool; the full one is kept until
+/// [`Normalizer::reset_descriptor_sets`] or drop, as recorded commands may
+/// still use its sets.
+pub struct Normalizer {
+    device: ash::Device,
+    set_layout: vk::DescriptorSetLayout,
+    pipeline_layout: vk::PipelineLayout,
+    descriptor_pool: vk::DescriptorPool,
+    full_pools: Vec<vk::DescriptorPool>,
+    pipelines: [vk::Pipeline; 3],
+    sets: Vec<((vk::Buffer, u64), vk::DescriptorSet)>,
+}
+
+impl Normalizer {
+    /// Create the layouts and descriptor pool on `device`.
+    ///
+    /// # Safety
+    /// `device` must outlive the normalizer.
+    pub unsafe fn new(device: &ash::Device) -> Result<Self, VkfftError> {
+        let binding = vk::DescriptorSetLayoutBinding {
+            binding: 0,
+            descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+            descriptor_count: 1,
+            stage_flags: vk::ShaderStageFlags::COMPUTE,
+            ..Default::default()
+        };
+        let set_layout_info = vk::DescriptorSetLayoutCreateInfo {
+            s_type: vk::StructureType::DESCRIPTOR_SET_LAYOUT_CREATE_INFO,
+            binding_count: 1,
+            p_bindings: &binding,
+            ..Default::default()
+        };
+        let set_layout = device.create_descriptor_set_layout(&set_layout_info, None)?;
+
+        // Large enough for the f64 variant: double scale + uint count.
+        let push_range = vk::PushConstantRange {
+            stage_flags: vk::ShaderStageFlags::COMPUTE,
+            offset: 0,
+            size: 16,
+        };
+        let pipeline_layout_info = vk::PipelineLayoutCreateInfo {
+            s_type: vk::StructureType::PIPELINE_LAYOUT_CREATE_INFO,
+            set_layout_count: 1,
+            p_set_layouts: &set_layout,
+            push_constant_range_count: 1,
+            p_push_constant_ranges: &push_range,
+            ..Default::default()
+        };
+        let pipeline_layout = match device.create_pipeline_layout(&pipeline_layout_info, None) {
+            Ok(layout) => layout,
+            Err(err) => {
+                device.destroy_descriptor_set_layout(set_layout, None);
+                return Err(err.into());
+            }
+        };
+
+        let descriptor_pool = match create_descriptor_pool(device) {
+            Ok(pool) => pool,
+            Err(err) => {
+                device.destroy_pipeline_layout(pipeline_layout, None);
+                device.destroy_descriptor_set_layout(set_layout, None);
+                return Err(err.into());
+            }
+        };
+
+        Ok(Self {
+            device: device.clone(),
+            set_layout,
+            pipeline_layout,
+            descriptor_pool,
+            full_pools: Vec::new(),
+            pipelines: [vk::Pipeline::null(); 3],
+            sets: Vec::new(),
+        })
+    }
+
+    /// Drop cached descriptor sets, e.g. after the buffers they point to were
+    /// destroyed. Must not be called while recorded commands are pending.
+    pub fn reset_descriptor_sets(&mut self) -> Result<(), VkfftError> {
+        unsafe {
+            for pool in self.full_pools.drain(..) {
+                self.device.destroy_descriptor_pool(pool, None);
+            }
+            self.device.reset_descriptor_pool(
+                self.descriptor_pool,
+                vk::DescriptorPoolResetFlags::empty(),
+            )?;
+        }
+        self.sets.clear();
+        Ok(())
+    }
+
+    fn pipeline(&mut self, storage: ScalarStorage) -> Result<vk::Pipeline, VkfftError> {
+        let slot = storage.index();
+        if self.pipelines[slot] != vk::Pipeline::null() {
+            return Ok(self.pipelines[slot]);
+        }
+
+        let spv = match storage {
+            ScalarStorage::Half => SCALE_F16_SPV,
+            ScalarStorage::Single => SCALE_F32_SPV,
+            ScalarStorage::Double => SCALE_F64_SPV,
+        };
+        let code: Vec<u32> = spv
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+        let module_info = vk::ShaderModuleCreateInfo {
+            s_type: vk::StructureType::SHADER_MODULE_CREATE_INFO,
+            code_size: spv.len(),
+            p_code: code.as_ptr(),
+            ..Default::default()
+        };
+        let module = unsafe { self.device.create_shader_module(&module_info, None)? };
+
+        let stage = vk::PipelineShaderStageCreateInfo {
+            s_type: vk::StructureType::PIPELINE_SHADER_STAGE_CREATE_INFO,
+            stage: vk::ShaderStageFlags::COMPUTE,
+            module,
+            p_name: c"main".as_ptr(),
+            ..Default::default()
+        };
+        let pipeline_info = vk::ComputePipelineCreateInfo {
+            s_type: vk::StructureType::COMPUTE_PIPELINE_CREATE_INFO,
+            stage,
+            layout: self.pipeline_layout,
+            ..Default::default()
+        };
+        let result = unsafe {
+            self.device
+                .create_compute_pipelines(vk::PipelineCache::null(), &[pipeline_info], None)
+        };
+        unsafe { self.device.destroy_shader_module(module, None) };
+
+        let pipeline = result.map_err(|(_, err)| err)?[0];
+        self.pipelines[slot] = pipeline;
+        Ok(pipeline)
+    }
+
+    fn descriptor_set(
+        &mut self,
+        buffer: vk::Buffer,
+        size: u64,
+    ) -> Result<vk::DescriptorSet, VkfftError> {
+        if let Some((_, set)) = self.sets.iter().find(|(key, _)| *key == (buffer, size)) {
+            return Ok(*set);
+        }
+
+        let set = match self.allocate_set() {
+            Ok(set) => set,
+            Err(vk::Result::ERROR_OUT_OF_POOL_MEMORY | vk::Result::ERROR_FRAGMENTED_POOL) => {
+                let pool = unsafe { create_descriptor_pool(&self.device)? };
+                self.full_pools
+                    .push(core::mem::replace(&mut self.descriptor_pool, pool));
+                self.sets.clear();
+                self.allocate_set()?
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let buffer_info = vk::DescriptorBufferInfo {
+            buffer,
+            offset: 0,
+            range: size,
+        };
+        let write = vk::WriteDescriptorSet {
+            s_type: vk::StructureType::WRITE_DESCRIPTOR_SET,
+            dst_set: set,
+            dst_binding: 0,
+            descriptor_count: 1,
+            descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+            p_buffer_info: &buffer_info,
+            ..Default::default()
+        };
+        unsafe { self.device.update_descriptor_sets(&[write], &[]) };
+
+        self.sets.push(((buffer, size), set));
+        Ok(set)
+    }
+
+    fn allocate_set(&self) -> Result<vk::DescriptorSet, vk::Result> {
+        let allocate_info = vk::DescriptorSetAllocateInfo {
+            s_type: vk::StructureType::DESCRIPTOR_SET_ALLOCATE_INFO,
+            descriptor_pool: self.descriptor_pool,
+            descriptor_set_count: 1,
+            p_set_layouts: &self.set_layout,
+            ..Default::default()
+        };
+        Ok(unsafe { self.device.allocate_descriptor_sets(&allocate_info)? }[0])
+    }
+
+    fn record_scale(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        buffer: vk::Buffer,
+        size: u64,
+        storage: ScalarStorage,
+        factor: f64,
+    ) -> Result<(), VkfftError> {
+        let pipeline = self.pipeline(storage)?;
+        let set = self.descriptor_set(buffer, size)?;
+
+        // Half data is processed as packed pairs, i.e. in 4-byte words.
+        let count = match storage {
+            ScalarStorage::Half | ScalarStorage::Single => size / 4,
+            ScalarStorage::Double => size / 8,
+        };
+        let mut push = [0u8; 16];
+        match storage {
+            ScalarStorage::Double => {
+                push[..8].copy_from_slice(&factor.to_le_bytes());
+                push[8..12].copy_from_slice(&(count as u32).to_le_bytes());
+            }
+            _ => {
+                push[..4].copy_from_slice(&(factor as f32).to_le_bytes());
+                push[4..8].copy_from_slice(&(count as u32).to_le_bytes());
+            }
+        }
+        let groups = count.div_ceil(WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS) as u32;
+
+        unsafe {
+            self.device
+                .cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::COMPUTE, pipeline);
+            self.device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                self.pipeline_layout,
+                0,
+                &[set],
+                &[],
+            );
+            self.device.cmd_push_constants(
+                command_buffer,
+                self.pipeline_layout,
+                vk::ShaderStageFlags::COMPUTE,
+                0,
+                &push,
+            );
+            self.device.cmd_dispatch(command_buffer, groups, 1, 1);
+        }
+        Ok(())
+    }
+
+    fn record_barrier(&self, command_buffer: vk::CommandBuffer) {
+        let barrier = vk::MemoryBarrier {
+            s_type: vk::StructureType::MEMORY_BARRIER,
+            src_access_mask: vk::AccessFlags::SHADER_WRITE,
+            dst_access_mask: vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+            ..Default::default()
+        };
+        unsafe {
+            self.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::DependencyFlags::empty(),
+                &[barrier],
+                &[],
+                &[],
+            );
+        }
+    }
+}
+
+unsafe fn create_descriptor_pool(device: &ash::Device) -> Result<vk::DescriptorPool, vk::Result> {
+    let pool_size = vk::DescriptorPoolSize {
+        ty: vk::DescriptorType::STORAGE_BUFFER,
+        descriptor_count: MAX_DESCRIPTOR_SETS,
+    };
+    let pool_info = vk::DescriptorPoolCreateInfo {
+        s_type: vk::StructureType::DESCRIPTOR_POOL_CREATE_INFO,
+        max_sets: MAX_DESCRIPTOR_SETS,
+        pool_size_count: 1,
+        p_pool_sizes: &pool_size,
+        ..Default::default()
+    };
+    device.create_descriptor_pool(&pool_info, None)
+}
+
+impl Drop for Normalizer {
+    fn drop(&mut self) {
+        unsafe {
+            for pipeline in self.pipelines {
+                if pipeline != vk::Pipeline::null() {
+                    self.device.destroy_pipeline(pipeline, None);
+                }
+            }
+            for pool in self.full_pools.drain(..) {
+                self.device.destroy_descriptor_pool(pool, None);
+            }
+            self.device
+                .destroy_descriptor_pool(self.descriptor_pool, None);
+            self.device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+            self.device
+                .destroy_descriptor_set_layout(self.set_layout, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_factors_match_numpy() {
+        assert_eq!(Normalization::Backward.extra_scale(64, false), None);
+        assert_eq!(Normalization::Backward.extra_scale(64, true), None);
+        assert_eq!(
+            Normalization::Forward.extra_scale(64, false),
+            Some(1.0 / 64.0)
+        );
+        assert_eq!(Normalization::Forward.extra_scale(64, true), None);
+        assert_eq!(Normalization::Ortho.extra_scale(64, false), Some(0.125));
+        assert_eq!(Normalization::Ortho.extra_scale(64, true), Some(0.125));
+        assert_eq!(Normalization::None.extra_scale(64, true), None);
+
+        let ortho = Normalization::Ortho.extra_scale(1000, true).unwrap();
+        assert!((ortho * ortho * 1000.0 - 1.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn plan_length_skips_omitted_axes() {
+        let mut fft = VkFft::new();
+        fft.configure_dimensions(&[32, 8, 4]);
+        fft.set_axis_transform(1, false).unwrap();
+        fft.set_normalization(Normalization::Forward);
+        assert_eq!(fft.transform_length(), 128);
+        assert_eq!(fft.config.normalize, 0);
+        assert_eq!(fft.scale_factor(-1), Some(1.0 / 128.0));
+        assert_eq!(fft.scale_factor(1), None);
+
+        fft.set_normalization(Normalization::Backward);
+        assert_eq!(fft.config.normalize, 1);
+        assert_eq!(fft.scale_factor(1), None);
+    }
+
+    #[test]
+    fn plain_append_rejects_extra_scaling() {
+        let mut fft = VkFft::new();
+        fft.configure_dimensions(&[64]);
+        fft.set_normalization(Normalization::Ortho);
+        let err = fft.append(vk::CommandBuffer::null(), 1).unwrap_err();
+        assert_eq!(
+            err,
+            VkfftError::InvalidArgument(
+                "append: Ortho and Forward normalization need append_normalized"
+            )
+        );
+
+        // Backward is VkFFT's own normalize flag; only the missing plan fails.
+        fft.set_normalization(Normalization::Backward);
+        let err = fft.append(vk::CommandBuffer::null(), 1).unwrap_err();
+        assert_eq!(
+            err,
+            VkfftError::Vkfft(crate::ffi::VkFFTResult::VKFFT_ERROR_PLAN_NOT_INITIALIZED)
+        );
+    }
+
+    #[test]
+    fn scales_the_buffers_written_last() {
+        use ash::vk::Handle;
+        let [input, buffer, output] = [1, 2, 3].map(vk::Buffer::from_raw);
+
+        // fp16 storage computed in fp32: the fp32 working buffer is twice
+        // the size of the fp16 output, which is what gets scaled.
+        let mut fft = VkFft::new();
+        fft.configure_formatted_buffers(input, 1024, buffer, 2048, output, 1024)
+            .unwrap();
+        assert_eq!(fft.scale_targets().unwrap(), [(output, 1024)]);
+
+        let mut fft = VkFft::new();
+        fft.configure_buffers(&[buffer], None).unwrap();
+        assert!(fft.scale_targets().is_err());
+        fft.set_buffer_sizes(&[2048]);
+        assert_eq!(fft.scale_targets().unwrap(), [(buffer, 2048)]);
+
+        // Out of place, the working buffer sizes say nothing about the output.
+        fft.configure_buffers(&[buffer], Some(&[output])).unwrap();
+        fft.set_buffer_sizes(&[2048]);
+        assert!(fft.scale_targets().is_err());
+        fft.set_output_buffer_sizes(&[1024]);
+        assert_eq!(fft.scale_targets().unwrap(), [(output, 1024)]);
+    }
+
+    #[test]
+    fn embedded_spirv_is_well_formed() {
+        for spv in [SCALE_F16_SPV, SCALE_F32_SPV, SCALE_F64_SPV] {
+            assert_eq!(spv.len() % 4, 0);
+            assert_eq!(&spv[..4], &0x0723_0203u32.to_le_bytes());
+        }
+    }
+}
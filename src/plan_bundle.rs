@@ -0,0 +1,458 @@
+//! Ahead-of-time compiled plans for one device.
+//!
+//! A [`PlanBundle`] holds plans saved with [`VkFft::save_plan`] on a
+//! particular device and driver, each keyed by the [`fingerprint`] of the
+//! configuration it was compiled for. The `vkfft-plan-compiler` binary
+//! writes bundles; at run time a plan configured the same way picks its
+//! compiled kernels up with [`VkFft::load_plan_from_bundle`] instead of
+//! compiling shaders.
+//!
+//! The binary form is little-endian: the magic `VKFFTBDL`, a `u32` format
+//! version, the `u32` VkFFT version, the device (`u32` vendor, device and
+//! driver IDs, the 16-byte pipeline cache UUID and a length-prefixed name),
+//! a `u32` entry count, and per entry the `u64` fingerprint, a
+//! length-prefixed label and the `u64`-length-prefixed saved plan.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+
+use ash::vk;
+
+use crate::version::{version, Version};
+use crate::vkfft::{VkFft, VkfftError};
+
+const MAGIC: [u8; 8] = *b"VKFFTBDL";
+const FORMAT_VERSION: u32 = 1;
+
+const MALFORMED: &str = "plan bundle: malformed or truncated";
+
+/// Hash of every configuration field that shapes the generated kernels.
+///
+/// Fields VkFFT derives from the device (shared memory size, workgroup
+/// limits, subgroup size) are left out; bundles record the device
+/// separately. A shared memory size other than the detected limit is a
+/// tuning choice and is included, as is the block size of split buffers,
+/// which the kernels index by.
+pub fn fingerprint(plan: &VkFft) -> u64 {
+    let config = &plan.config;
+    let detected_shared_memory = plan
+        .device_limits
+        .map_or(0, |limits| limits.max_shared_memory);
+    let shared_memory_override = if config.sharedMemorySize == detected_shared_memory {
+        0
+    } else {
+        config.sharedMemorySize
+    };
+    let mut hash = Fnv::new();
+    hash.words(&[config.FFTdim]);
+    hash.words(&config.size);
+    hash.words(&[
+        config.numberBatches.max(1),
+        config.performR2C,
+        config.performDCT,
+        config.performDST,
+        config.doublePrecision,
+        config.doublePrecisionFloatMemory,
+        config.halfPrecision,
+        config.halfPrecisionMemoryOnly,
+        config.quadDoubleDoublePrecision,
+        config.quadDoubleDoublePrecisionDoubleMemory,
+        config.normalize,
+        config.useUint64,
+        config.inverseReturnToInputBuffer,
+        config.isInputFormatted,
+        config.isOutputFormatted,
+        config.considerAllAxesStrided,
+        config.performConvolution,
+        config.frequencyZeroPadding,
+    ]);
+    hash.words(&[config.bufferNum.max(1)]);
+    if config.bufferNum > 1 {
+        hash.words(&plan.buffer_sizes[..plan.buffer_sizes.len().min(1)]);
+    }
+    hash.words(&config.omitDimension);
+    hash.words(&config.bufferStride);
+    hash.words(&config.inputBufferStride);
+    hash.words(&config.outputBufferStride);
+    hash.words(&config.performZeropadding);
+    hash.words(&config.fft_zeropad_left);
+    hash.words(&config.fft_zeropad_right);
+    // Tuning.
+    hash.words(&config.groupedBatch);
+    hash.words(&[
+        config.coalescedMemory,
+        config.numSharedMemoryBanks,
+        config.aimThreads,
+        config.maxThreadsNum,
+        config.registerBoost,
+        config.registerBoostNonPow2,
+        config.registerBoost4Step,
+        config.performBandwidthBoost as u64,
+        config.useLUT as u64,
+        config.useLUT_4step as u64,
+        config.disableReorderFourStep,
+        config.swapTo3Stage4Step,
+        config.fixMaxRadixBluestein,
+        config.useCustomBluesteinPaddingPattern,
+        shared_memory_override,
+    ]);
+    hash.words(&plan.bluestein_primes);
+    hash.words(&plan.bluestein_padded);
+    hash.0
+}
+
+/// FNV-1a over little-endian words.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn words(&mut self, words: &[u64]) {
+        for word in words {
+            for byte in word.to_le_bytes() {
+                self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        }
+    }
+}
+
+/// The device and driver a bundle was compiled on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleDevice {
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub driver_version: u32,
+    pub pipeline_cache_uuid: [u8; vk::UUID_SIZE],
+    pub name: String,
+}
+
+impl BundleDevice {
+    pub fn from_properties(properties: &vk::PhysicalDeviceProperties) -> Self {
+        let name = unsafe { CStr::from_ptr(properties.device_name.as_ptr()) };
+        Self {
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            driver_version: properties.driver_version,
+            pipeline_cache_uuid: properties.pipeline_cache_uuid,
+            name: name.to_string_lossy().into_owned(),
+        }
+    }
+
+    /// Whether kernels compiled on this device run on `properties`' device:
+    /// same vendor, device, driver and pipeline cache UUID.
+    pub fn matches(&self, properties: &vk::PhysicalDeviceProperties) -> bool {
+        self.vendor_id == properties.vendor_id
+            && self.device_id == properties.device_id
+            && self.driver_version == properties.driver_version
+            && self.pipeline_cache_uuid == properties.pipeline_cache_uuid
+    }
+}
+
+/// One compiled plan.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleEntry {
+    /// Free-form description, e.g. the name given in the compiler's input.
+    pub label: String,
+    pub fingerprint: u64,
+    /// The plan as returned by [`VkFft::save_plan`].
+    pub plan: Vec<u8>,
+}
+
+/// Compiled plans for one device, produced by one VkFFT release.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanBundle {
+    device: BundleDevice,
+    entries: Vec<BundleEntry>,
+}
+
+impl PlanBundle {
+    pub fn new(device: BundleDevice) -> Self {
+        Self {
+            device,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn device(&self) -> &BundleDevice {
+        &self.device
+    }
+
+    pub fn entries(&self) -> &[BundleEntry] {
+        &self.entries
+    }
+
+    /// Add `plan`, which must have been initialized with
+    /// [`VkFft::set_plan_saving`] enabled. Replaces an entry with the same
+    /// fingerprint.
+    pub fn add(&mut self, label: impl Into<String>, plan: &VkFft) -> Result<(), VkfftError> {
+        let entry = BundleEntry {
+            label: label.into(),
+            fingerprint: fingerprint(plan),
+            plan: plan.save_plan()?,
+        };
+        match self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.fingerprint == entry.fingerprint)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+        Ok(())
+    }
+
+    pub fn find(&self, fingerprint: u64) -> Option<&BundleEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.fingerprint == fingerprint)
+    }
+
+    /// Fail unless the bundle was compiled on the device described by
+    /// `properties` (see [`BundleDevice::matches`]).
+    pub fn check_device(
+        &self,
+        properties: &vk::PhysicalDeviceProperties,
+    ) -> Result<(), VkfftError> {
+        if self.device.matches(properties) {
+            Ok(())
+        } else {
+            Err(VkfftError::InvalidArgument(
+                "plan bundle: compiled for a different device or driver",
+            ))
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&version().raw().to_le_bytes());
+        let device = &self.device;
+        for id in [device.vendor_id, device.device_id, device.driver_version] {
+            out.extend_from_slice(&id.to_le_bytes());
+        }
+        out.extend_from_slice(&device.pipeline_cache_uuid);
+        put_str(&mut out, &device.name);
+        out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            out.extend_from_slice(&entry.fingerprint.to_le_bytes());
+            put_str(&mut out, &entry.label);
+            out.extend_from_slice(&(entry.plan.len() as u64).to_le_bytes());
+            out.extend_from_slice(&entry.plan);
+        }
+        out
+    }
+
+    /// Parse a bundle written by [`PlanBundle::to_bytes`].
+    ///
+    /// # Errors
+    /// `VkfftError::PlanVersion` if the bundle was produced by a different
+    /// VkFFT release.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VkfftError> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != FORMAT_VERSION {
+            return Err(VkfftError::InvalidArgument(
+                "plan bundle: not a plan bundle or an unsupported format version",
+            ));
+        }
+        let plan = Version::from_raw(reader.u32()?);
+        let library = version();
+        if plan != library {
+            return Err(VkfftError::PlanVersion { plan, library });
+        }
+        let device = BundleDevice {
+            vendor_id: reader.u32()?,
+            device_id: reader.u32()?,
+            driver_version: reader.u32()?,
+            pipeline_cache_uuid: reader.take(vk::UUID_SIZE)?.try_into().unwrap(),
+            name: reader.string()?,
+        };
+        let count = reader.u32()?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let fingerprint = reader.u64()?;
+            let label = reader.string()?;
+            let len = usize::try_from(reader.u64()?).map_err(|_| MALFORMED)?;
+            let plan = reader.take(len)?.to_vec();
+            entries.push(BundleEntry {
+                label,
+                fingerprint,
+                plan,
+            });
+        }
+        if !reader.0.is_empty() {
+            return Err(MALFORMED.into());
+        }
+        Ok(Self { device, entries })
+    }
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], VkfftError> {
+        if self.0.len() < len {
+            return Err(MALFORMED.into());
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, VkfftError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, VkfftError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, VkfftError> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| MALFORMED.into())
+    }
+}
+
+impl VkFft {
+    /// Load this plan's compiled kernels from `bundle`; call after
+    /// configuring the plan and before `initialize`.
+    ///
+    /// The bundle's device is not checked here; use
+    /// [`PlanBundle::check_device`] once after reading it.
+    ///
+    /// # Errors
+    /// Fails if the bundle has no plan with this configuration.
+    ///
+    /// # Safety
+    /// As for [`VkFft::load_plan`]: the bundle must have been written by
+    /// [`PlanBundle`] for a device and driver that
+    /// [`PlanBundle::check_device`] accepts for this plan's device, since the
+    /// kernels in it are loaded without validation.
+    pub unsafe fn load_plan_from_bundle(&mut self, bundle: &PlanBundle) -> Result<(), VkfftError> {
+        let entry = bundle
+            .find(fingerprint(self))
+            .ok_or(VkfftError::InvalidArgument(
+                "plan bundle: no plan compiled for this configuration",
+            ))?;
+        self.load_plan(&entry.plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device_limits::DeviceLimits;
+    use crate::serialized_plan::frame;
+    use alloc::string::ToString;
+    use ash::vk::Handle;
+
+    fn device() -> BundleDevice {
+        BundleDevice {
+            vendor_id: 0x10de,
+            device_id: 0x2684,
+            driver_version: 0x8f_8000,
+            pipeline_cache_uuid: [7; vk::UUID_SIZE],
+            name: "NVIDIA GeForce RTX 4090".to_string(),
+        }
+    }
+
+    fn plan(sizes: &[u64]) -> VkFft {
+        let mut fft = VkFft::new();
+        fft.configure_dimensions(sizes);
+        fft
+    }
+
+    #[test]
+    fn fingerprint_follows_the_configuration() {
+        let base = fingerprint(&plan(&[1024]));
+        assert_eq!(base, fingerprint(&plan(&[1024])));
+        assert_ne!(base, fingerprint(&plan(&[2048])));
+
+        let mut fft = plan(&[1024]);
+        fft.set_double_precision(true);
+        assert_ne!(base, fingerprint(&fft));
+
+        // Split buffers change the addressing, unlike the size of a single one.
+        let mut fft = plan(&[1024]);
+        fft.configure_buffers(&[vk::Buffer::from_raw(1)], None)
+            .unwrap();
+        fft.set_buffer_sizes(&[8192]);
+        assert_eq!(base, fingerprint(&fft));
+        let buffers = [1, 2].map(vk::Buffer::from_raw);
+        fft.configure_split_buffer(&buffers, &[4096, 4096]).unwrap();
+        let split = fingerprint(&fft);
+        assert_ne!(base, split);
+        fft.configure_split_buffer(&buffers, &[6144, 2048]).unwrap();
+        assert_ne!(split, fingerprint(&fft));
+
+        // Device limits are recorded by the bundle, not the fingerprint.
+        let mut fft = plan(&[1024]);
+        fft.set_device_limits(DeviceLimits {
+            max_shared_memory: 48 * 1024,
+            subgroup_size: 32,
+            ..Default::default()
+        });
+        assert_eq!(base, fingerprint(&fft));
+
+        // Tuning the shared memory size below the limit changes the kernels.
+        fft.config.sharedMemorySize = 32 * 1024;
+        assert_ne!(base, fingerprint(&fft));
+        let mut fft = plan(&[1024]);
+        fft.config.sharedMemorySize = 32 * 1024;
+        assert_ne!(base, fingerprint(&fft));
+    }
+
+    #[test]
+    fn bundles_round_trip() {
+        let mut bundle = PlanBundle::new(device());
+        assert!(bundle.add("unsaved", &plan(&[64])).is_err());
+
+        bundle.entries.push(BundleEntry {
+            label: "c2c 1024".to_string(),
+            fingerprint: fingerprint(&plan(&[1024])),
+            plan: frame(version(), b"kernels"),
+        });
+        let bytes = bundle.to_bytes();
+        assert_eq!(PlanBundle::from_bytes(&bytes).unwrap(), bundle);
+        assert!(PlanBundle::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(PlanBundle::from_bytes(b"VKFFTPLN").is_err());
+
+        // Bundles from another VkFFT release are refused up front.
+        let mut other = bytes.clone();
+        let raw = version().raw() + 1;
+        other[12..16].copy_from_slice(&raw.to_le_bytes());
+        assert!(matches!(
+            PlanBundle::from_bytes(&other),
+            Err(VkfftError::PlanVersion { .. })
+        ));
+
+        let mut fft = plan(&[2048]);
+        assert!(unsafe { fft.load_plan_from_bundle(&bundle) }.is_err());
+        let mut fft = plan(&[1024]);
+        unsafe { fft.load_plan_from_bundle(&bundle).unwrap() };
+        assert_eq!(fft.config.loadApplicationFromString, 1);
+    }
+
+    #[test]
+    fn checks_the_device() {
+        let bundle = PlanBundle::new(device());
+        let mut properties = vk::PhysicalDeviceProperties {
+            vendor_id: 0x10de,
+            device_id: 0x2684,
+            driver_version: 0x8f_8000,
+            pipeline_cache_uuid: [7; vk::UUID_SIZE],
+            ..Default::default()
+        };
+        assert!(bundle.check_device(&properties).is_ok());
+        properties.driver_version += 1;
+        assert!(bundle.check_device(&properties).is_err());
+    }
+}
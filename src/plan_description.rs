@@ -0,0 +1,300 @@
+//! Plain-data descriptions of FFT plans.
+//!
+//! A [`PlanDescription`] records everything the `configure_*` and `set_*`
+//! methods would set on a plan except the Vulkan handles and buffers: the
+//! transform shape and kind, precision, normalization, batching, strides,
+//! Bluestein padding and tuning. It can be stored in configuration files
+//! (with the `serde` feature), compared and hashed to find identical plans,
+//! and turned into a [`VkFft`] with [`PlanDescription::build`] or
+//! [`DeviceContext::create_plan_from`].
+
+use alloc::vec::Vec;
+
+use crate::bluestein::BluesteinPadding;
+use crate::context::{DeviceContext, Plan};
+use crate::element::{Precision, PrecisionFlags};
+use crate::normalization::Normalization;
+pub use crate::transform::TransformKind;
+use crate::tuning::TuningOptions;
+use crate::vkfft::{VkFft, VkfftError};
+
+/// Everything that configures a plan apart from its device and buffers.
+///
+/// Descriptions compare and hash field by field, so `compute: None` and
+/// `compute: Some(precision)` are different keys even though they build the
+/// same plan. With the `serde` feature, missing fields take the values of
+/// [`PlanDescription::default`] and unknown fields are rejected.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct PlanDescription {
+    /// Axis lengths, contiguous axis first (1 to 4 axes).
+    pub size: Vec<u64>,
+    pub kind: TransformKind,
+    /// Precision of the values in the buffers.
+    pub precision: Precision,
+    /// Precision VkFFT computes in; `None` computes at `precision`.
+    pub compute: Option<Precision>,
+    pub normalization: Normalization,
+    /// Number of transforms in the buffer (`numberBatches`).
+    pub batch: u64,
+    /// Axes to transform, `None` for all of them. Skipped axes act as
+    /// further batch dimensions; every axis must be below `size.len()`.
+    pub transformed_axes: Option<Vec<usize>>,
+    /// Element strides of the buffer; `None` keeps the packed layout.
+    pub buffer_stride: Option<[u64; 4]>,
+    pub input_stride: Option<[u64; 4]>,
+    pub output_stride: Option<[u64; 4]>,
+    /// Custom Bluestein padding table, empty for VkFFT's own choice.
+    pub bluestein_padding: Vec<BluesteinPadding>,
+    pub max_radix_bluestein: Option<u64>,
+    pub tuning: TuningOptions,
+}
+
+impl Default for PlanDescription {
+    /// A single single-precision complex transform with no size set.
+    fn default() -> Self {
+        Self {
+            size: Vec::new(),
+            kind: TransformKind::C2c,
+            precision: Precision::Single,
+            compute: None,
+            normalization: Normalization::None,
+            batch: 1,
+            transformed_axes: None,
+            buffer_stride: None,
+            input_stride: None,
+            output_stride: None,
+            bluestein_padding: Vec::new(),
+            max_radix_bluestein: None,
+            tuning: TuningOptions::default(),
+        }
+    }
+}
+
+impl PlanDescription {
+    /// A complex transform of the given axis lengths, contiguous axis first.
+    pub fn new(size: &[u64]) -> Self {
+        Self {
+            size: size.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    /// A new plan configured from this description. Vulkan handles and
+    /// buffers still have to be configured before `initialize`.
+    ///
+    /// # Errors
+    ///
+    /// See [`PlanDescription::apply`].
+    pub fn build(&self) -> Result<VkFft, VkfftError> {
+        let mut plan = VkFft::new();
+        self.apply(&mut plan)?;
+        Ok(plan)
+    }
+
+    /// Configure `plan` from this description, replacing every setting the
+    /// description covers.
+    ///
+    /// # Errors
+    ///
+    /// `VkfftError::InvalidArgument` for sizes outside 1 to 4 positive axes,
+    /// a zero batch count, an unsupported precision combination, or padding
+    /// and tuning values the `set_*` methods reject. `VkfftError::Vkfft` for
+    /// transformed axes that are not below `size.len()`.
+    pub fn apply(&self, plan: &mut VkFft) -> Result<(), VkfftError> {
+        if self.size.is_empty() || self.size.len() > 4 {
+            return Err(VkfftError::InvalidArgument(
+                "PlanDescription: size must have 1 to 4 axes",
+            ));
+        }
+        if self.size.contains(&0) {
+            return Err(VkfftError::InvalidArgument(
+                "PlanDescription: sizes must be positive",
+            ));
+        }
+        if self.batch == 0 {
+            return Err(VkfftError::InvalidArgument(
+                "PlanDescription: batch must be positive",
+            ));
+        }
+        let compute = self.compute.unwrap_or(self.precision);
+        plan.set_precision_flags(PrecisionFlags::new(self.precision, compute)?);
+        plan.configure_dimensions(&self.size);
+        plan.set_batch_count(self.batch);
+        plan.set_r2c(self.kind == TransformKind::R2c);
+        plan.set_dct(self.kind.dct_type())?;
+        match &self.transformed_axes {
+            Some(axes) => plan.set_transformed_axes(axes)?,
+            None => plan.set_transformed_axes(&[0, 1, 2, 3][..self.size.len()])?,
+        }
+        plan.set_strides(
+            self.buffer_stride.unwrap_or_default(),
+            Some(self.input_stride.unwrap_or_default()),
+            Some(self.output_stride.unwrap_or_default()),
+        );
+        plan.set_normalization(self.normalization);
+        plan.set_bluestein_padding(&self.bluestein_padding)?;
+        plan.set_max_radix_bluestein(self.max_radix_bluestein)?;
+        plan.set_tuning(&self.tuning)?;
+        Ok(())
+    }
+}
+
+impl DeviceContext {
+    /// [`DeviceContext::create_plan`] configured from `description`.
+    ///
+    /// Tuning is checked against the device's limits.
+    ///
+    /// # Errors
+    ///
+    /// See [`PlanDescription::apply`].
+    pub fn create_plan_from(&self, description: &PlanDescription) -> Result<Plan<'_>, VkfftError> {
+        let mut plan = self.create_plan();
+        description.apply(&mut plan)?;
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan_bundle::fingerprint;
+
+    #[test]
+    fn builds_the_described_plan() {
+        let description = PlanDescription {
+            kind: TransformKind::Dct2,
+            precision: Precision::Half,
+            compute: Some(Precision::Single),
+            normalization: Normalization::Ortho,
+            batch: 3,
+            transformed_axes: Some([1].to_vec()),
+            buffer_stride: Some([64, 4096, 4096, 4096]),
+            bluestein_padding: [BluesteinPadding {
+                max_length: 67,
+                padded: 135,
+            }]
+            .to_vec(),
+            ..PlanDescription::new(&[64, 64])
+        };
+        let plan = description.build().unwrap();
+        assert_eq!(plan.config.FFTdim, 2);
+        assert_eq!(plan.config.size, [64, 64, 1, 1]);
+        assert_eq!(plan.config.numberBatches, 3);
+        assert_eq!(plan.config.performDCT, 2);
+        assert_eq!(plan.config.halfPrecisionMemoryOnly, 1);
+        assert_eq!(plan.config.omitDimension, [1, 0, 0, 0]);
+        assert_eq!(plan.config.bufferStride, [64, 4096, 4096, 4096]);
+        assert_eq!(plan.config.useCustomBluesteinPaddingPattern, 1);
+        assert_eq!(plan.normalization(), Normalization::Ortho);
+
+        // Applying a description resets what an earlier one set.
+        let mut plan = plan;
+        PlanDescription::new(&[64, 64]).apply(&mut plan).unwrap();
+        assert_eq!(
+            fingerprint(&plan),
+            fingerprint(&PlanDescription::new(&[64, 64]).build().unwrap())
+        );
+    }
+
+    #[test]
+    fn described_axes_pass_validation() {
+        for size in [&[64][..], &[64, 64], &[64, 64, 8], &[64, 64, 8, 2]] {
+            let plan = PlanDescription::new(size).build().unwrap();
+            assert!(plan.validate_omitted_axes().is_ok(), "{size:?}");
+        }
+        let plan = PlanDescription {
+            transformed_axes: Some([0, 2].to_vec()),
+            ..PlanDescription::new(&[64, 64, 8])
+        }
+        .build()
+        .unwrap();
+        assert_eq!(plan.config.omitDimension, [0, 1, 0, 0]);
+        assert!(plan.validate_omitted_axes().is_ok());
+    }
+
+    #[test]
+    fn equal_descriptions_build_equal_plans() {
+        let a = PlanDescription {
+            kind: TransformKind::R2c,
+            batch: 4,
+            ..PlanDescription::new(&[1000])
+        };
+        let b = a.clone();
+        assert_eq!(a, b);
+        assert_eq!(
+            fingerprint(&a.build().unwrap()),
+            fingerprint(&b.build().unwrap())
+        );
+        assert_ne!(a, PlanDescription::new(&[1000]));
+    }
+
+    #[test]
+    fn rejects_invalid_descriptions() {
+        assert!(PlanDescription::default().build().is_err());
+        assert!(PlanDescription::new(&[8, 8, 8, 8, 8]).build().is_err());
+        assert!(PlanDescription::new(&[8, 0]).build().is_err());
+        let invalid = [
+            PlanDescription {
+                batch: 0,
+                ..PlanDescription::new(&[8])
+            },
+            PlanDescription {
+                precision: Precision::Double,
+                compute: Some(Precision::Single),
+                ..PlanDescription::new(&[8])
+            },
+            PlanDescription {
+                transformed_axes: Some([4].to_vec()),
+                ..PlanDescription::new(&[8])
+            },
+            PlanDescription {
+                transformed_axes: Some([1].to_vec()),
+                ..PlanDescription::new(&[8])
+            },
+            PlanDescription {
+                max_radix_bluestein: Some(17),
+                ..PlanDescription::new(&[8])
+            },
+        ];
+        for description in invalid {
+            assert!(description.build().is_err(), "{description:?}");
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_serde() {
+        let description = PlanDescription {
+            kind: TransformKind::R2c,
+            precision: Precision::Half,
+            compute: Some(Precision::Single),
+            normalization: Normalization::Backward,
+            input_stride: Some([1002, 1002, 1002, 1002]),
+            tuning: TuningOptions {
+                aim_threads: Some(256),
+                grouped_batch: [0, 4, 0, 0],
+                ..TuningOptions::default()
+            },
+            ..PlanDescription::new(&[1000])
+        };
+        let json = serde_json::to_string(&description).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PlanDescription>(&json).unwrap(),
+            description
+        );
+
+        let parsed: PlanDescription = serde_json::from_str(
+            r#"{"size": [1000], "kind": "r2c", "precision": "half", "compute": "single",
+                "normalization": "backward", "input_stride": [1002, 1002, 1002, 1002],
+                "tuning": {"aim_threads": 256, "grouped_batch": [0, 4, 0, 0]}}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed, description);
+        assert!(serde_json::from_str::<PlanDescription>(r#"{"sizes": [8]}"#).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! Introspection of an initialized plan.
+//!
+//! VkFFT decides on its own how to split each axis into kernels (single
+//! upload or four-step), whether to use Rader's or Bluestein's algorithm for
+//! hard sizes and how much scratch memory to allocate. [`VkFft::plan_info`]
+//! reads those decisions back out of `VkFFTApplication`.
+
+use alloc::vec::Vec;
+
+use crate::ffi;
+use crate::VkFft;
+
+/// What VkFFT built for a plan.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanInfo {
+    /// Axes of the forward plan (`localFFTPlan`). Omitted axes are skipped.
+    pub forward: Vec<AxisInfo>,
+    /// Axes of the separate inverse plan (`localFFTPlan_inverse`), which VkFFT
+    /// only builds when the inverse needs different kernels.
+    pub inverse: Option<Vec<AxisInfo>>,
+    /// Batch count VkFFT dispatches with.
+    pub actual_batches: u64,
+    /// Temporary buffer VkFFT allocated itself, in bytes (0 if none).
+    pub temp_buffer_bytes: u64,
+}
+
+/// How one axis is executed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AxisInfo {
+    /// VkFFT axis index (0 is the contiguous axis).
+    pub axis: usize,
+    /// One kernel per upload; more than one means a four-step decomposition.
+    pub kernels: Vec<KernelInfo>,
+    /// Length handled by each upload (`axisSplit`); their product is the
+    /// axis length, or the padded Bluestein length.
+    pub splits: Vec<u64>,
+    /// Whether the axis goes through Bluestein's algorithm.
+    pub bluestein: bool,
+    /// Size of the precomputed Bluestein kernel buffer in bytes. The buffer
+    /// belongs to the application, so the forward and inverse plans report
+    /// the same one.
+    pub bluestein_buffer_bytes: u64,
+    /// Whether the axis performs the real-to-complex step.
+    pub r2c: bool,
+}
+
+/// One compute kernel of an axis.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KernelInfo {
+    /// Workgroup size (`axisBlock`).
+    pub workgroup_size: [u64; 3],
+    /// Radix of every stage, in order.
+    pub stage_radices: Vec<u32>,
+    /// Whether any stage uses Rader's algorithm.
+    pub rader: bool,
+    pub registers_per_thread: u64,
+    /// Size of the twiddle lookup table in bytes (0 when computed on the fly).
+    pub lut_bytes: u64,
+    /// Whether the lookup table is another kernel's (`referenceLUT`), so
+    /// `lut_bytes` are not allocated again.
+    pub shared_lut: bool,
+}
+
+impl PlanInfo {
+    /// Number of kernels dispatched by one forward transform.
+    pub fn kernel_count(&self) -> usize {
+        self.forward.iter().map(|axis| axis.kernels.len()).sum()
+    }
+
+    /// Whether any forward axis needs more than one upload.
+    pub fn uses_four_step(&self) -> bool {
+        self.forward.iter().any(|axis| axis.kernels.len() > 1)
+    }
+
+    /// Whether any forward axis uses Bluestein's algorithm.
+    pub fn uses_bluestein(&self) -> bool {
+        self.forward.iter().any(|axis| axis.bluestein)
+    }
+
+    /// Whether any forward kernel uses Rader's algorithm.
+    pub fn uses_rader(&self) -> bool {
+        self.forward
+            .iter()
+            .flat_map(|axis| &axis.kernels)
+            .any(|kernel| kernel.rader)
+    }
+
+    /// Device memory VkFFT allocated for the plan: temp buffer, lookup
+    /// tables and Bluestein kernels, in bytes.
+    ///
+    /// Shared lookup tables are counted once, and Bluestein kernels once per
+    /// axis.
+    pub fn device_memory_bytes(&self) -> u64 {
+        let bluestein: u64 = self
+            .forward
+            .iter()
+            .map(|axis| axis.bluestein_buffer_bytes)
+            .sum();
+        let luts: u64 = self
+            .forward
+            .iter()
+            .chain(self.inverse.iter().flatten())
+            .flat_map(|axis| &axis.kernels)
+            .filter(|kernel| !kernel.shared_lut)
+            .map(|kernel| kernel.lut_bytes)
+            .sum();
+        self.temp_buffer_bytes + bluestein + luts
+    }
+}
+
+impl VkFft {
+    /// Describe the kernels VkFFT built, or `None` before `initialize`.
+    pub fn plan_info(&self) -> Option<PlanInfo> {
+        if !self.initialized || self.app.localFFTPlan.is_null() {
+            return None;
+        }
+        let forward = unsafe { self.axes_info(&*self.app.localFFTPlan) };
+        let inverse =
+            unsafe { self.app.localFFTPlan_inverse.as_ref() }.map(|plan| self.axes_info(plan));
+
+        let config = &self.app.configuration;
+        let temp_buffer_bytes =
+            if config.allocateTempBuffer != 0 && !config.tempBufferSize.is_null() {
+                unsafe { *config.tempBufferSize }
+            } else {
+                0
+            };
+
+        Some(PlanInfo {
+            forward,
+            inverse,
+            actual_batches: self.app.actualNumBatches,
+            temp_buffer_bytes,
+        })
+    }
+
+    fn axes_info(&self, plan: &ffi::VkFFTPlan) -> Vec<AxisInfo> {
+        let dims = (self.app.configuration.FFTdim as usize).min(plan.numAxisUploads.len());
+        (0..dims)
+            .filter(|&axis| plan.numAxisUploads[axis] > 0)
+            .map(|axis| {
+                let uploads = (plan.numAxisUploads[axis] as usize).min(plan.axes[axis].len());
+                let kernels = plan.axes[axis][..uploads].iter().map(kernel_info).collect();
+                AxisInfo {
+                    axis,
+                    kernels,
+                    splits: plan.axisSplit[axis][..uploads].to_vec(),
+                    bluestein: self.app.useBluesteinFFT[axis] != 0,
+                    bluestein_buffer_bytes: self.app.bufferBluesteinSize[axis],
+                    r2c: plan.actualPerformR2CPerAxis[axis] != 0,
+                }
+            })
+            .collect()
+    }
+}
+
+fn kernel_info(axis: &ffi::VkFFTAxis) -> KernelInfo {
+    let constants = &axis.specializationConstants;
+    let stages = (constants.numStages.max(0) as usize).min(constants.stageRadix.len());
+    KernelInfo {
+        workgroup_size: [axis.axisBlock[0], axis.axisBlock[1], axis.axisBlock[2]],
+        stage_radices: constants.stageRadix[..stages]
+            .iter()
+            .map(|&radix| radix as u32)
+            .collect(),
+        rader: constants.useRader != 0,
+        registers_per_thread: constants.registers_per_thread as u64,
+        lut_bytes: axis.bufferLUTSize,
+        shared_lut: axis.referenceLUT != 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+
+    #[test]
+    fn none_before_initialize() {
+        assert!(VkFft::new().plan_info().is_none());
+    }
+
+    #[test]
+    fn reads_axis_splits_and_kernels() {
+        let mut plan: Box<ffi::VkFFTPlan> = unsafe { Box::new_zeroed().assume_init() };
+        plan.numAxisUploads[0] = 2;
+        plan.axisSplit[0][0] = 64;
+        plan.axisSplit[0][1] = 32;
+        plan.axes[0][0].axisBlock = [64, 1, 1, 1];
+        plan.axes[0][0].specializationConstants.numStages = 2;
+        plan.axes[0][0].specializationConstants.stageRadix[0] = 8;
+        plan.axes[0][0].specializationConstants.stageRadix[1] = 8;
+        plan.axes[0][1].bufferLUTSize = 256;
+
+        let mut fft = VkFft::new();
+        fft.app.configuration.FFTdim = 2;
+        fft.app.localFFTPlan = &mut *plan;
+        fft.app.useBluesteinFFT[0] = 1;
+        fft.app.bufferBluesteinSize[0] = 1024;
+        fft.initialized = true;
+
+        let info = fft.plan_info().unwrap();
+        // Axis 1 has no uploads, as if it were omitted.
+        assert_eq!(info.forward.len(), 1);
+        assert_eq!(info.forward[0].splits, [64, 32]);
+        assert_eq!(info.forward[0].kernels[0].stage_radices, [8, 8]);
+        assert_eq!(info.forward[0].kernels[0].workgroup_size, [64, 1, 1]);
+        assert!(info.uses_four_step() && info.uses_bluestein() && !info.uses_rader());
+        assert_eq!(info.kernel_count(), 2);
+        assert_eq!(info.device_memory_bytes(), 1024 + 256);
+        assert!(info.inverse.is_none());
+
+        // An inverse plan reusing the forward tables adds no memory.
+        let mut inverse = plan.clone();
+        inverse.axes[0][1].referenceLUT = 1;
+        fft.app.localFFTPlan_inverse = &mut *inverse;
+        let info = fft.plan_info().unwrap();
+        assert_eq!(
+            info.inverse.as_ref().unwrap()[0].bluestein_buffer_bytes,
+            1024
+        );
+        assert_eq!(info.device_memory_bytes(), 1024 + 256);
+
+        // The plans were never created by VkFFT; keep Drop from deleting them.
+        fft.initialized = false;
+        fft.app.localFFTPlan = core::ptr::null_mut();
+        fft.app.localFFTPlan_inverse = core::ptr::null_mut();
+    }
+}
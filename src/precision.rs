@@ -0,0 +1,14 @@
+//! Floating point precisions of VkFFT's buffers and kernels.
+
+/// Floating point precision used for storage or computation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Precision {
+    Half,
+    Single,
+    Double,
+}
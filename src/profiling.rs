@@ -0,0 +1,258 @@
+//! GPU timestamp profiling for recorded transforms.
+//!
+//! When profiling is enabled, [`VkFft::append`] brackets the dispatches it
+//! records with `vkCmdWriteTimestamp` into a query pool owned by the plan.
+//! [`VkFft::take_timings`] reads back the queries whose command buffers have
+//! executed and converts them to durations with the device's
+//! `timestampPeriod`.
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use ash::vk;
+
+use crate::device_limits::DeviceLimits;
+use crate::vkfft::{VkFft, VkfftError};
+
+/// GPU time spent in one recorded `append`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FftTiming {
+    /// Direction passed to `append`.
+    pub direction: i32,
+    /// Time between the timestamps written before and after the dispatches.
+    pub duration: Duration,
+}
+
+/// Query pool and bookkeeping for profiled appends.
+pub(crate) struct GpuProfiler {
+    device: ash::Device,
+    pool: vk::QueryPool,
+    /// Nanoseconds per timestamp tick.
+    period: f64,
+    mask: u64,
+    capacity: u32,
+    next: u32,
+    /// Slots written and not yet read back, oldest first.
+    pending: Vec<(u32, i32)>,
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    emit_spans: bool,
+}
+
+impl GpuProfiler {
+    /// Write the starting timestamp for a new dispatch and return its slot.
+    pub(crate) unsafe fn begin(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        direction: i32,
+    ) -> u32 {
+        let slot = self.next;
+        self.next = (self.next + 1) % self.capacity;
+        // Reusing a slot drops the unread result that lived in it.
+        self.pending.retain(|&(pending, _)| pending != slot);
+        self.pending.push((slot, direction));
+
+        // The host reset makes the slot read as unavailable until this
+        // command buffer executes, so a never-submitted append cannot be
+        // resolved from stale or uninitialized queries. The recorded reset
+        // keeps a slot reused within one command buffer valid.
+        self.device.reset_query_pool(self.pool, 2 * slot, 2);
+        self.device
+            .cmd_reset_query_pool(command_buffer, self.pool, 2 * slot, 2);
+        self.device.cmd_write_timestamp(
+            command_buffer,
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            self.pool,
+            2 * slot,
+        );
+        slot
+    }
+
+    /// Write the closing timestamp for `slot`.
+    pub(crate) unsafe fn end(&mut self, command_buffer: vk::CommandBuffer, slot: u32) {
+        self.device.cmd_write_timestamp(
+            command_buffer,
+            vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+            self.pool,
+            2 * slot + 1,
+        );
+    }
+
+    /// Read back the pending slots whose timestamps are available; the rest
+    /// stay pending.
+    unsafe fn resolve(&mut self) -> Result<Vec<FftTiming>, VkfftError> {
+        let mut timings = Vec::with_capacity(self.pending.len());
+        let mut waiting = Vec::new();
+        for &(slot, direction) in &self.pending {
+            // Each query yields its timestamp followed by its availability.
+            let mut stamps = [[0u64; 2]; 2];
+            match self.device.get_query_pool_results(
+                self.pool,
+                2 * slot,
+                &mut stamps,
+                vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WITH_AVAILABILITY,
+            ) {
+                Ok(()) | Err(vk::Result::NOT_READY) => {}
+                Err(err) => return Err(err.into()),
+            }
+            if stamps[0][1] == 0 || stamps[1][1] == 0 {
+                waiting.push((slot, direction));
+                continue;
+            }
+            let ticks = stamps[1][0].wrapping_sub(stamps[0][0]) & self.mask;
+            let duration = Duration::from_nanos((ticks as f64 * self.period) as u64);
+            timings.push(FftTiming {
+                direction,
+                duration,
+            });
+        }
+        self.pending = waiting;
+
+        #[cfg(feature = "tracing")]
+        if self.emit_spans {
+            for timing in &timings {
+                let span = tracing::info_span!(
+                    "vkfft.gpu",
+                    direction = timing.direction,
+                    duration_ns = timing.duration.as_nanos() as u64,
+                );
+                span.in_scope(|| {});
+            }
+        }
+        Ok(timings)
+    }
+}
+
+impl Drop for GpuProfiler {
+    fn drop(&mut self) {
+        unsafe { self.device.destroy_query_pool(self.pool, None) };
+    }
+}
+
+impl VkFft {
+    /// Enable GPU timestamp profiling of `append`.
+    ///
+    /// Up to `capacity` appends can be recorded between calls to
+    /// [`VkFft::take_timings`]; older unread results are overwritten.
+    /// `queue_family_index` is the family the command buffers are submitted to
+    /// and is used to check timestamp support. Call after `configure_vulkan`.
+    ///
+    /// Queries are reset on the host, so `device` must have been created with
+    /// the Vulkan 1.2 `hostQueryReset` feature enabled ([`FftContext`] enables
+    /// it when supported).
+    ///
+    /// # Errors
+    /// Returns an error if the queue family has no timestamp support, the
+    /// device does not support `hostQueryReset`, or the query pool cannot be
+    /// created.
+    ///
+    /// # Safety
+    /// `instance` and `device` must be the ones the plan was configured with,
+    /// `hostQueryReset` must be enabled on `device`, and the plan must not
+    /// outlive `device`. Every `capacity`-th append reuses a slot: the command
+    /// buffer holding the append recorded `capacity` appends earlier must
+    /// have finished executing (or never been submitted) before recording
+    /// the next one.
+    ///
+    /// [`FftContext`]: crate::fft_context::FftContext
+    pub unsafe fn enable_profiling(
+        &mut self,
+        instance: &ash::Instance,
+        device: &ash::Device,
+        queue_family_index: u32,
+        capacity: u32,
+    ) -> Result<(), VkfftError> {
+        if capacity == 0 {
+            return Err(VkfftError::InvalidArgument(
+                "enable_profiling: capacity must be at least 1",
+            ));
+        }
+        let families = instance.get_physical_device_queue_family_properties(self.phys);
+        let bits = families
+            .get(queue_family_index as usize)
+            .map_or(0, |family| family.timestamp_valid_bits);
+        if bits == 0 {
+            return Err(VkfftError::InvalidArgument(
+                "enable_profiling: queue family does not support timestamps",
+            ));
+        }
+        let properties = instance.get_physical_device_properties(self.phys);
+        if !DeviceLimits::query(instance, self.phys).host_query_reset {
+            return Err(VkfftError::InvalidArgument(
+                "enable_profiling: device does not support hostQueryReset",
+            ));
+        }
+
+        let pool_info = vk::QueryPoolCreateInfo {
+            s_type: vk::StructureType::QUERY_POOL_CREATE_INFO,
+            query_type: vk::QueryType::TIMESTAMP,
+            query_count: 2 * capacity,
+            ..Default::default()
+        };
+        let pool = device.create_query_pool(&pool_info, None)?;
+        // Unused slots must read as unavailable rather than undefined.
+        device.reset_query_pool(pool, 0, 2 * capacity);
+
+        self.profiler = Some(GpuProfiler {
+            device: device.clone(),
+            pool,
+            period: properties.limits.timestamp_period as f64,
+            mask: if bits >= 64 {
+                u64::MAX
+            } else {
+                (1u64 << bits) - 1
+            },
+            capacity,
+            next: 0,
+            pending: Vec::new(),
+            emit_spans: false,
+        });
+        Ok(())
+    }
+
+    /// Stop profiling and release the query pool.
+    ///
+    /// # Safety
+    /// No submitted command buffer may still reference the query pool.
+    pub unsafe fn disable_profiling(&mut self) {
+        self.profiler = None;
+    }
+
+    pub fn profiling_enabled(&self) -> bool {
+        self.profiler.is_some()
+    }
+
+    /// Emit a `vkfft.gpu` span with `direction` and `duration_ns` fields per
+    /// timing returned by [`VkFft::take_timings`]. Only has an
+    /// effect with the `tracing` feature.
+    pub fn set_profiling_spans(&mut self, enabled: bool) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.emit_spans = enabled;
+        }
+    }
+
+    /// Read back the timings of the profiled appends that have finished
+    /// executing, oldest first. Returns an empty list when profiling is off.
+    ///
+    /// Does not block: appends whose command buffers are still executing (or
+    /// were never submitted) stay pending and are returned by a later call
+    /// once their timestamps are available.
+    pub fn take_timings(&mut self) -> Result<Vec<FftTiming>, VkfftError> {
+        match self.profiler.as_mut() {
+            Some(profiler) => unsafe { profiler.resolve() },
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_are_empty_without_profiling() {
+        let mut fft = VkFft::new();
+        assert!(!fft.profiling_enabled());
+        fft.set_profiling_spans(true);
+        assert_eq!(fft.take_timings().unwrap(), Vec::new());
+    }
+}
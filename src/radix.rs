@@ -0,0 +1,17 @@
+//! Factorization helpers shared by the length queries and Bluestein tuning.
+
+/// Primes VkFFT has dedicated radix kernels for.
+pub(crate) const RADICES: [u64; 6] = [2, 3, 5, 7, 11, 13];
+
+/// Whether every prime factor of `n` is `<= max_prime`.
+pub(crate) fn is_smooth(mut n: u64, max_prime: u64) -> bool {
+    if n == 0 {
+        return false;
+    }
+    for &p in RADICES.iter().filter(|&&p| p <= max_prime) {
+        while n.is_multiple_of(p) {
+            n /= p;
+        }
+    }
+    n == 1
+}
@@ -0,0 +1,140 @@
+//! Serialized plans tagged with the VkFFT release that produced them.
+//!
+//! VkFFT can save an initialized application (its compiled kernels) and
+//! load it later to skip shader compilation, but the saved string carries
+//! no version and loading one from another release is undefined behavior.
+//! [`VkFft::save_plan`] prefixes it with a header naming the release, and
+//! [`VkFft::load_plan`] refuses plans from any other release.
+
+use alloc::vec::Vec;
+
+use crate::version::{version, Version};
+use crate::vkfft::{VkFft, VkfftError};
+
+const MAGIC: [u8; 8] = *b"VKFFTPLN";
+/// Magic, `u32` raw version and `u64` payload length, little-endian.
+const HEADER_LEN: usize = 20;
+
+const NOT_A_PLAN: &str = "plan: not a serialized VkFFT plan";
+
+/// VkFFT release that produced a plan saved by [`VkFft::save_plan`].
+pub fn plan_version(plan: &[u8]) -> Result<Version, VkfftError> {
+    split(plan).map(|(version, _)| version)
+}
+
+fn split(plan: &[u8]) -> Result<(Version, &[u8]), VkfftError> {
+    if plan.len() < HEADER_LEN || plan[..8] != MAGIC {
+        return Err(VkfftError::InvalidArgument(NOT_A_PLAN));
+    }
+    let raw = u32::from_le_bytes(plan[8..12].try_into().unwrap());
+    let len = u64::from_le_bytes(plan[12..20].try_into().unwrap());
+    let payload = &plan[HEADER_LEN..];
+    if payload.len() as u64 != len {
+        return Err(VkfftError::InvalidArgument(
+            "plan: serialized plan is truncated",
+        ));
+    }
+    Ok((Version::from_raw(raw), payload))
+}
+
+pub(crate) fn frame(version: Version, payload: &[u8]) -> Vec<u8> {
+    let mut plan = Vec::with_capacity(HEADER_LEN + payload.len());
+    plan.extend_from_slice(&MAGIC);
+    plan.extend_from_slice(&version.raw().to_le_bytes());
+    plan.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    plan.extend_from_slice(payload);
+    plan
+}
+
+impl VkFft {
+    /// Keep the compiled application after `initialize` so that
+    /// [`VkFft::save_plan`] can return it.
+    pub fn set_plan_saving(&mut self, enabled: bool) {
+        self.config.saveApplicationToString = enabled.into();
+    }
+
+    /// The initialized plan, serialized for [`VkFft::load_plan`].
+    ///
+    /// # Errors
+    /// Fails unless the plan was initialized with saving enabled through
+    /// [`VkFft::set_plan_saving`].
+    pub fn save_plan(&self) -> Result<Vec<u8>, VkfftError> {
+        if !self.initialized
+            || self.config.saveApplicationToString == 0
+            || self.app.saveApplicationString.is_null()
+        {
+            return Err(VkfftError::InvalidArgument(
+                "plan: initialize with set_plan_saving(true) before saving",
+            ));
+        }
+        let payload = unsafe {
+            core::slice::from_raw_parts(
+                self.app.saveApplicationString.cast::<u8>(),
+                self.app.applicationStringSize as usize,
+            )
+        };
+        Ok(frame(version(), payload))
+    }
+
+    /// Initialize from a plan saved by [`VkFft::save_plan`] instead of
+    /// compiling shaders. The configuration must match the one the plan was
+    /// saved with; call before `initialize`.
+    ///
+    /// # Errors
+    /// `VkfftError::PlanVersion` if the plan was produced by a different
+    /// VkFFT release than the one compiled into this build.
+    ///
+    /// # Safety
+    /// Only the release is checked. `plan` must come from
+    /// [`VkFft::save_plan`] on a plan with this configuration, built for the
+    /// same device and driver: VkFFT reads the payload without validation
+    /// and hands the kernels in it to the driver as they are.
+    pub unsafe fn load_plan(&mut self, plan: &[u8]) -> Result<(), VkfftError> {
+        let (plan_version, payload) = split(plan)?;
+        let library = version();
+        if plan_version != library {
+            return Err(VkfftError::PlanVersion {
+                plan: plan_version,
+                library,
+            });
+        }
+        self.plan_string.clear();
+        self.plan_string.extend_from_slice(payload);
+        self.config.loadApplicationFromString = 1;
+        self.config.loadApplicationString = self.plan_string.as_mut_ptr().cast();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_plans_from_other_releases() {
+        let library = version();
+        let plan = frame(library, b"kernels");
+        assert_eq!(plan_version(&plan).unwrap(), library);
+
+        let mut fft = VkFft::new();
+        unsafe { fft.load_plan(&plan).unwrap() };
+        assert_eq!(fft.config.loadApplicationFromString, 1);
+        assert_eq!(fft.plan_string, b"kernels");
+
+        let older = Version::from_raw(library.raw() - 1);
+        let mut fft = VkFft::new();
+        let err = unsafe { fft.load_plan(&frame(older, b"kernels")) }.unwrap_err();
+        assert!(matches!(err, VkfftError::PlanVersion { plan, .. } if plan == older));
+        assert_eq!(fft.config.loadApplicationFromString, 0);
+
+        assert!(unsafe { fft.load_plan(b"kernels") }.is_err());
+        assert!(unsafe { fft.load_plan(&plan[..plan.len() - 1]) }.is_err());
+    }
+
+    #[test]
+    fn saving_requires_an_initialized_plan() {
+        let mut fft = VkFft::new();
+        fft.set_plan_saving(true);
+        assert!(fft.save_plan().is_err());
+    }
+}
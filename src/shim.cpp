@@ -1,6 +1,186 @@
 // src/shim.cpp
+#include <vulkan/vulkan.h>
+#include "glslang_c_interface.h"
+#include "vkfft_trace.h"
+
+#if defined(VKFFT_DLOPEN_GLSLANG) || defined(VKFFT_PRECOMPILED_ONLY)
+#define VKFFT_FORWARD_GLSLANG
+#endif
+
+#ifdef VKFFT_DLOPEN_GLSLANG
+#include <cstdlib>
+#ifdef _WIN32
+#include <windows.h>
+#else
+#include <dlfcn.h>
+#endif
+#endif
+
 extern "C" {
 
+#ifdef VKFFT_FORWARD_GLSLANG
+// glslang is not linked: the functions VkFFT calls are defined here and
+// forward to the library opened by vkfft_glslang_load(). Until it loads (and
+// always with VKFFT_PRECOMPILED_ONLY) they return zero/null, so only plans
+// loaded from application strings work.
+#define GLSLANG_COMPILER_FUNCTIONS(X)                                                              \
+    X(glslang_shader_t*, glslang_shader_create, (const glslang_input_t* input), (input))           \
+    X(int, glslang_shader_preprocess, (glslang_shader_t* shader, const glslang_input_t* input),    \
+      (shader, input))                                                                             \
+    X(int, glslang_shader_parse, (glslang_shader_t* shader, const glslang_input_t* input),         \
+      (shader, input))                                                                             \
+    X(const char*, glslang_shader_get_info_log, (glslang_shader_t* shader), (shader))              \
+    X(const char*, glslang_shader_get_info_debug_log, (glslang_shader_t* shader), (shader))        \
+    X(void, glslang_shader_delete, (glslang_shader_t* shader), (shader))                           \
+    X(glslang_program_t*, glslang_program_create, (void), ())                                      \
+    X(void, glslang_program_add_shader, (glslang_program_t* program, glslang_shader_t* shader),    \
+      (program, shader))                                                                           \
+    X(int, glslang_program_link, (glslang_program_t* program, int messages), (program, messages))  \
+    X(void, glslang_program_SPIRV_generate, (glslang_program_t* program, glslang_stage_t stage),   \
+      (program, stage))                                                                            \
+    X(size_t, glslang_program_SPIRV_get_size, (glslang_program_t* program), (program))             \
+    X(void, glslang_program_SPIRV_get, (glslang_program_t* program, unsigned int* out),            \
+      (program, out))                                                                              \
+    X(const char*, glslang_program_SPIRV_get_messages, (glslang_program_t* program), (program))    \
+    X(const char*, glslang_program_get_info_log, (glslang_program_t* program), (program))          \
+    X(const char*, glslang_program_get_info_debug_log, (glslang_program_t* program), (program))    \
+    X(void, glslang_program_delete, (glslang_program_t* program), (program))
+
+#define GLSLANG_FUNCTIONS(X)                                                                       \
+    GLSLANG_COMPILER_FUNCTIONS(X)                                                                  \
+    X(int, glslang_initialize_process, (void), ())                                                 \
+    X(void, glslang_finalize_process, (void), ())
+
+#define GLSLANG_POINTER(ret, name, params, args) static ret(*name##_ptr) params = nullptr;
+GLSLANG_FUNCTIONS(GLSLANG_POINTER)
+
+#define GLSLANG_FORWARD(ret, name, params, args)                                                   \
+    ret name params {                                                                              \
+        using result = ret;                                                                        \
+        return name##_ptr ? name##_ptr args : result();                                            \
+    }
+GLSLANG_COMPILER_FUNCTIONS(GLSLANG_FORWARD)
+
+// initializeVkFFT sets up the process even for plans loaded from
+// application strings, which compile nothing; succeed without glslang.
+int glslang_initialize_process(void) {
+    return glslang_initialize_process_ptr ? glslang_initialize_process_ptr() : 1;
+}
+
+void glslang_finalize_process(void) {
+    if (glslang_finalize_process_ptr) {
+        glslang_finalize_process_ptr();
+    }
+}
+
+#endif
+
+#ifdef VKFFT_DLOPEN_GLSLANG
+static void* open_glslang() {
+    const char* path = std::getenv("VKFFT_GLSLANG_LIBRARY");
+#ifdef _WIN32
+    return LoadLibraryA(path ? path : "glslang.dll");
+#else
+    if (path) {
+        return dlopen(path, RTLD_NOW | RTLD_LOCAL);
+    }
+    static const char* const names[] = {
+        "libglslang.so.15", "libglslang.so.14", "libglslang.so.13", "libglslang.so.12",
+        "libglslang.so.11", "libglslang.so",    "libglslang.dylib",
+    };
+    for (const char* name : names) {
+        if (void* handle = dlopen(name, RTLD_NOW | RTLD_LOCAL)) {
+            return handle;
+        }
+    }
+    return nullptr;
+#endif
+}
+
+static void* glslang_symbol(void* handle, const char* name) {
+#ifdef _WIN32
+    return reinterpret_cast<void*>(GetProcAddress(static_cast<HMODULE>(handle), name));
+#else
+    return dlsym(handle, name);
+#endif
+}
+
+static bool load_glslang() {
+    void* handle = open_glslang();
+    if (!handle) {
+        return false;
+    }
+    bool complete = true;
+#define GLSLANG_RESOLVE(ret, name, params, args)                                                   \
+    name##_ptr = reinterpret_cast<ret(*) params>(glslang_symbol(handle, #name));                   \
+    complete = complete && name##_ptr;
+    GLSLANG_FUNCTIONS(GLSLANG_RESOLVE)
+    if (!complete) {
+        // A library missing any function is treated as no library at all.
+#define GLSLANG_RESET(ret, name, params, args) name##_ptr = nullptr;
+        GLSLANG_FUNCTIONS(GLSLANG_RESET)
+    }
+    return complete;
+}
+
+int vkfft_glslang_load(void) {
+    static const bool loaded = load_glslang();
+    return loaded;
+}
+#elif defined(VKFFT_PRECOMPILED_ONLY)
+int vkfft_glslang_load(void) {
+    return 0;
+}
+#else
+int vkfft_glslang_load(void) {
+    return 1;
+}
+#endif
+
+// Trace hook: called around shader compilation and pipeline creation while
+// initializeVkFFT runs. The calls are interposed by renaming them before
+// VkFFT's headers are included; their declarations above are unaffected.
+static thread_local vkfft_trace_hook trace_hook = nullptr;
+static thread_local void* trace_user = nullptr;
+
+static void trace(int event, int result) {
+    if (trace_hook) {
+        trace_hook(trace_user, event, result);
+    }
+}
+
+void vkfft_set_trace_hook(vkfft_trace_hook hook, void* user) {
+    trace_hook = hook;
+    trace_user = user;
+}
+
+static glslang_shader_t* traced_glslang_shader_create(const glslang_input_t* input) {
+    trace(VKFFT_TRACE_COMPILE_BEGIN, 0);
+    return glslang_shader_create(input);
+}
+
+static void traced_glslang_program_SPIRV_generate(glslang_program_t* program, glslang_stage_t stage) {
+    glslang_program_SPIRV_generate(program, stage);
+    trace(VKFFT_TRACE_COMPILE_END, 0);
+}
+
+static VkResult traced_vkCreateComputePipelines(
+    VkDevice device,
+    VkPipelineCache cache,
+    uint32_t count,
+    const VkComputePipelineCreateInfo* infos,
+    const VkAllocationCallbacks* allocator,
+    VkPipeline* pipelines) {
+    trace(VKFFT_TRACE_PIPELINE_BEGIN, 0);
+    VkResult res = vkCreateComputePipelines(device, cache, count, infos, allocator, pipelines);
+    trace(VKFFT_TRACE_PIPELINE_END, (int)res);
+    return res;
+}
+
+#define glslang_shader_create traced_glslang_shader_create
+#define glslang_program_SPIRV_generate traced_glslang_program_SPIRV_generate
+#define vkCreateComputePipelines traced_vkCreateComputePipelines
+
 // Include VkFFT (and thus inline definitions)
 #include "vkFFT.h"
 #include "vkFFT/vkFFT_AppManagement/vkFFT_InitializeApp.h"
@@ -20,5 +200,8 @@ void vkfft_delete(VkFFTApplication* app) {
     deleteVkFFT(app);
 }
 
-} // extern "C"
+int vkfft_get_version(void) {
+    return VkFFTGetVersion();
+}
 
+} // extern "C"
@@ -0,0 +1,183 @@
+//! `tracing` instrumentation for plan initialization and dispatch.
+//!
+//! Spans (all at `INFO` except `vkfft.append`, which is `DEBUG`):
+//!
+//! - `vkfft.initialize` with `dims`, `size`, `precision`, `batch` and the
+//!   resulting `VkFFTResult`, containing
+//!   - `vkfft.validate` for the wrapper's own configuration checks,
+//!   - `vkfft.shader_generation`, `vkfft.shader_compile` and
+//!     `vkfft.pipeline_creation` for every kernel VkFFT builds.
+//! - `vkfft.append` with `direction` and `result`.
+//!
+//! VkFFT compiles one kernel per upload of every axis (and again for the
+//! inverse plan when it differs), so the kernel phases carry a running
+//! `kernel` index rather than an axis number. The shim reports compilation
+//! and pipeline creation through `vkfft_set_trace_hook`; the time before a
+//! kernel is compiled is attributed to its shader generation, and the last
+//! `vkfft.shader_generation` span covers whatever setup VkFFT does after its
+//! final pipeline.
+//!
+//! Without the `tracing` feature the helpers here call straight through.
+
+use crate::ffi;
+
+#[cfg(feature = "tracing")]
+use tracing::{field, span::EnteredSpan, Span};
+
+/// Every label [`precision_label`] returns.
+pub(crate) const PRECISION_LABELS: [&str; 5] = [
+    "single",
+    "double",
+    "half",
+    "half-storage",
+    "single-storage-double-compute",
+];
+
+/// Short name for the precision flags set in `config`.
+pub(crate) fn precision_label(config: &ffi::VkFFTConfiguration) -> &'static str {
+    if config.halfPrecisionMemoryOnly != 0 {
+        "half-storage"
+    } else if config.halfPrecision != 0 {
+        "half"
+    } else if config.doublePrecisionFloatMemory != 0 {
+        "single-storage-double-compute"
+    } else if config.doublePrecision != 0 {
+        "double"
+    } else {
+        "single"
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn initialize_span(config: &ffi::VkFFTConfiguration) -> Span {
+    let dims = (config.FFTdim as usize).min(config.size.len());
+    tracing::info_span!(
+        "vkfft.initialize",
+        dims = config.FFTdim,
+        size = ?&config.size[..dims],
+        precision = precision_label(config),
+        batch = config.numberBatches,
+        result = field::Empty,
+    )
+}
+
+/// Run the wrapper's configuration checks inside a `vkfft.validate` span.
+pub(crate) fn validation<T, E: core::fmt::Debug>(
+    check: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    #[cfg(feature = "tracing")]
+    let span = tracing::info_span!("vkfft.validate", result = field::Empty).entered();
+    let result = check();
+    #[cfg(feature = "tracing")]
+    if let Err(err) = &result {
+        span.record("result", field::debug(err));
+    }
+    result
+}
+
+/// Run `initialize` with the shim's trace hook installed so each kernel's
+/// phases show up as spans.
+pub(crate) fn kernel_phases(initialize: impl FnOnce() -> ffi::VkFFTResult) -> ffi::VkFFTResult {
+    #[cfg(feature = "tracing")]
+    {
+        let mut state = KernelTrace {
+            kernel: 0,
+            phase: Some(generation_span(0)),
+        };
+        unsafe {
+            ffi::vkfft_set_trace_hook(
+                Some(on_trace_event),
+                (&mut state as *mut KernelTrace).cast(),
+            );
+        }
+        let res = initialize();
+        unsafe { ffi::vkfft_set_trace_hook(None, core::ptr::null_mut()) };
+        res
+    }
+    #[cfg(not(feature = "tracing"))]
+    initialize()
+}
+
+#[cfg(feature = "tracing")]
+struct KernelTrace {
+    kernel: u32,
+    phase: Option<EnteredSpan>,
+}
+
+#[cfg(feature = "tracing")]
+fn generation_span(kernel: u32) -> EnteredSpan {
+    tracing::info_span!("vkfft.shader_generation", kernel).entered()
+}
+
+#[cfg(feature = "tracing")]
+unsafe extern "C" fn on_trace_event(
+    user: *mut core::ffi::c_void,
+    event: core::ffi::c_int,
+    result: core::ffi::c_int,
+) {
+    let state = &mut *user.cast::<KernelTrace>();
+    // Leave the current phase before entering the next one.
+    let previous = state.phase.take();
+    match event as u32 {
+        ffi::VKFFT_TRACE_COMPILE_BEGIN => {
+            drop(previous);
+            state.phase =
+                Some(tracing::info_span!("vkfft.shader_compile", kernel = state.kernel).entered());
+        }
+        ffi::VKFFT_TRACE_PIPELINE_BEGIN => {
+            drop(previous);
+            state.phase = Some(
+                tracing::info_span!(
+                    "vkfft.pipeline_creation",
+                    kernel = state.kernel,
+                    result = field::Empty
+                )
+                .entered(),
+            );
+        }
+        ffi::VKFFT_TRACE_PIPELINE_END => {
+            if let Some(span) = previous {
+                span.record("result", field::debug(ash::vk::Result::from_raw(result)));
+            }
+            state.kernel += 1;
+            state.phase = Some(generation_span(state.kernel));
+        }
+        // VKFFT_TRACE_COMPILE_END: shader module creation is not traced.
+        _ => drop(previous),
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernel_index_advances_after_each_pipeline() {
+        let mut state = KernelTrace {
+            kernel: 0,
+            phase: Some(generation_span(0)),
+        };
+        let user = (&mut state as *mut KernelTrace).cast();
+        let events = [
+            ffi::VKFFT_TRACE_COMPILE_BEGIN,
+            ffi::VKFFT_TRACE_COMPILE_END,
+            ffi::VKFFT_TRACE_PIPELINE_BEGIN,
+            ffi::VKFFT_TRACE_PIPELINE_END,
+            ffi::VKFFT_TRACE_COMPILE_BEGIN,
+        ];
+        for event in events {
+            unsafe { on_trace_event(user, event as core::ffi::c_int, 0) };
+        }
+        assert_eq!(state.kernel, 1);
+        assert!(state.phase.is_some());
+    }
+
+    #[test]
+    fn labels_precision_flags() {
+        let mut config = unsafe { core::mem::zeroed::<ffi::VkFFTConfiguration>() };
+        assert_eq!(precision_label(&config), "single");
+        config.halfPrecision = 1;
+        config.halfPrecisionMemoryOnly = 1;
+        assert_eq!(precision_label(&config), "half-storage");
+    }
+}
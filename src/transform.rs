@@ -0,0 +1,35 @@
+//! The kinds of transform VkFFT computes, shared by the length queries and
+//! plan descriptions.
+
+/// The transform a plan computes.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum TransformKind {
+    /// Complex-to-complex.
+    #[default]
+    C2c,
+    /// Real-to-complex forward, complex-to-real inverse.
+    R2c,
+    /// Discrete cosine transforms of types 1 to 4.
+    Dct1,
+    Dct2,
+    Dct3,
+    Dct4,
+}
+
+impl TransformKind {
+    /// The DCT type VkFFT's `performDCT` expects, 0 for the other kinds.
+    pub fn dct_type(self) -> u64 {
+        match self {
+            Self::C2c | Self::R2c => 0,
+            Self::Dct1 => 1,
+            Self::Dct2 => 2,
+            Self::Dct3 => 3,
+            Self::Dct4 => 4,
+        }
+    }
+}
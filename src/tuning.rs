@@ -0,0 +1,315 @@
+//! Performance tuning knobs.
+//!
+//! VkFFT picks workgroup sizes, coalescing, register usage and lookup table
+//! use from the device vendor and limits. [`TuningOptions`] overrides those
+//! choices per plan; every field left at its default keeps VkFFT's own
+//! heuristic. See [`crate::autotune`] for measuring which overrides pay off on
+//! a given device.
+
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+use crate::VkFft;
+
+/// Overrides for VkFFT's performance heuristics.
+///
+/// `None` (and `0` entries in `grouped_batch`) leave the value to VkFFT.
+/// Apply with [`VkFft::set_tuning`] before `initialize`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct TuningOptions {
+    /// Bytes read per coalesced memory transaction (`coalescedMemory`). VkFFT
+    /// uses 32 on Nvidia and 64 elsewhere. Must be a power of two.
+    pub coalesced_memory: Option<u64>,
+    /// Threads per workgroup VkFFT aims for (`aimThreads`, default 128).
+    pub aim_threads: Option<u64>,
+    /// Number of shared memory banks (`numSharedMemoryBanks`, default 32).
+    /// Must be a power of two.
+    pub shared_memory_banks: Option<u64>,
+    /// Factor by which the register file extends shared memory for
+    /// single-upload axes (`registerBoost`, default 1).
+    pub register_boost: Option<u64>,
+    /// `registerBoost` for four-step axes (`registerBoost4Step`, default 1).
+    pub register_boost_four_step: Option<u64>,
+    /// Factor by which coalescing is reduced on strided axes to fit longer
+    /// sequences in one upload (`performBandwidthBoost`). VkFFT enables it
+    /// for Bluestein axes; `Some(0)` turns it off everywhere.
+    pub bandwidth_boost: Option<u64>,
+    /// Read twiddle factors from a lookup table instead of computing them
+    /// (`useLUT`). VkFFT enables it for double precision and on some vendors.
+    pub lut: Option<bool>,
+    /// `useLUT` for the four-step twiddles between uploads (`useLUT_4step`).
+    pub lut_four_step: Option<bool>,
+    /// Skip the final transposition of four-step transforms, leaving the
+    /// output in a non-standard order (`disableReorderFourStep`).
+    pub disable_reorder_four_step: bool,
+    /// log2 of the axis length from which four-step transforms use three
+    /// uploads instead of two (`swapTo3Stage4Step`). VkFFT requires at
+    /// least 17.
+    pub three_upload_log2: Option<u32>,
+    /// Batches grouped into one workgroup, per axis (`groupedBatch`).
+    pub grouped_batch: [u64; 4],
+    /// Upper bound on threads per workgroup (`maxThreadsNum`). Defaults to
+    /// the device limit.
+    pub max_threads: Option<u64>,
+    /// Shared memory available per workgroup in bytes (`sharedMemorySize`).
+    /// Defaults to the device limit.
+    pub shared_memory_bytes: Option<u64>,
+}
+
+impl TuningOptions {
+    /// Check the values VkFFT cannot accept regardless of the device.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let power_of_two = |value: Option<u64>| value.is_none_or(u64::is_power_of_two);
+        let positive = |value: Option<u64>| value != Some(0);
+
+        if !power_of_two(self.coalesced_memory) {
+            return Err("TuningOptions: coalesced_memory must be a power of two");
+        }
+        if !power_of_two(self.shared_memory_banks) {
+            return Err("TuningOptions: shared_memory_banks must be a power of two");
+        }
+        if !positive(self.aim_threads) || !positive(self.max_threads) {
+            return Err("TuningOptions: thread counts must be positive");
+        }
+        if !positive(self.register_boost) || !positive(self.register_boost_four_step) {
+            return Err("TuningOptions: register boost factors must be at least 1");
+        }
+        if !positive(self.shared_memory_bytes) {
+            return Err("TuningOptions: shared_memory_bytes must be positive");
+        }
+        if self
+            .three_upload_log2
+            .is_some_and(|log2| !(17..=32).contains(&log2))
+        {
+            return Err("TuningOptions: three_upload_log2 must be between 17 and 32");
+        }
+        Ok(())
+    }
+
+    /// Write the options as space-separated `name=value` pairs, omitting
+    /// fields left to VkFFT. [`TuningOptions::parse_field`] reads them back.
+    pub fn write_fields(&self, out: &mut impl Write) -> fmt::Result {
+        let numbers = [
+            ("coalesced_memory", self.coalesced_memory),
+            ("aim_threads", self.aim_threads),
+            ("shared_memory_banks", self.shared_memory_banks),
+            ("register_boost", self.register_boost),
+            ("register_boost_four_step", self.register_boost_four_step),
+            ("bandwidth_boost", self.bandwidth_boost),
+            ("three_upload_log2", self.three_upload_log2.map(u64::from)),
+            ("max_threads", self.max_threads),
+            ("shared_memory_bytes", self.shared_memory_bytes),
+        ];
+        let flags = [("lut", self.lut), ("lut_four_step", self.lut_four_step)];
+
+        let mut separator = "";
+        for (name, value) in numbers {
+            if let Some(value) = value {
+                write!(out, "{separator}{name}={value}")?;
+                separator = " ";
+            }
+        }
+        for (name, value) in flags {
+            if let Some(value) = value {
+                write!(out, "{separator}{name}={}", value as u8)?;
+                separator = " ";
+            }
+        }
+        if self.disable_reorder_four_step {
+            write!(out, "{separator}disable_reorder_four_step=1")?;
+            separator = " ";
+        }
+        if self.grouped_batch != [0; 4] {
+            let [a, b, c, d] = self.grouped_batch;
+            write!(out, "{separator}grouped_batch={a},{b},{c},{d}")?;
+        }
+        Ok(())
+    }
+
+    /// Set the field `name` from its [`TuningOptions::write_fields`] form.
+    ///
+    /// Returns `Ok(false)` if `name` is not a tuning field.
+    pub fn parse_field(&mut self, name: &str, value: &str) -> Result<bool, &'static str> {
+        const BAD_VALUE: &str = "TuningOptions: malformed value";
+        let number = || value.parse::<u64>().map_err(|_| BAD_VALUE);
+        let flag = || match value {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(BAD_VALUE),
+        };
+
+        match name {
+            "coalesced_memory" => self.coalesced_memory = Some(number()?),
+            "aim_threads" => self.aim_threads = Some(number()?),
+            "shared_memory_banks" => self.shared_memory_banks = Some(number()?),
+            "register_boost" => self.register_boost = Some(number()?),
+            "register_boost_four_step" => self.register_boost_four_step = Some(number()?),
+            "bandwidth_boost" => self.bandwidth_boost = Some(number()?),
+            "three_upload_log2" => {
+                self.three_upload_log2 = Some(value.parse().map_err(|_| BAD_VALUE)?)
+            }
+            "max_threads" => self.max_threads = Some(number()?),
+            "shared_memory_bytes" => self.shared_memory_bytes = Some(number()?),
+            "lut" => self.lut = Some(flag()?),
+            "lut_four_step" => self.lut_four_step = Some(flag()?),
+            "disable_reorder_four_step" => self.disable_reorder_four_step = flag()?,
+            "grouped_batch" => {
+                let mut parts = value.split(',');
+                for slot in &mut self.grouped_batch {
+                    let part = parts.next().ok_or(BAD_VALUE)?;
+                    *slot = part.parse().map_err(|_| BAD_VALUE)?;
+                }
+                if parts.next().is_some() {
+                    return Err(BAD_VALUE);
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl fmt::Display for TuningOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fields = String::new();
+        self.write_fields(&mut fields)?;
+        if fields.is_empty() {
+            f.write_str("defaults")
+        } else {
+            f.write_str(&fields)
+        }
+    }
+}
+
+impl VkFft {
+    /// Replace the plan's performance overrides with `options`.
+    ///
+    /// Fields left at their defaults restore VkFFT's own choice, so applying
+    /// `TuningOptions::default()` undoes earlier tuning.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message from [`TuningOptions::validate`], or an error if
+    /// the options exceed limits detected by
+    /// [`VkFft::configure_vulkan_with_instance`]. Without detected limits,
+    /// values the device cannot run are reported by `initialize`.
+    pub fn set_tuning(&mut self, options: &TuningOptions) -> Result<(), &'static str> {
+        options.validate()?;
+        if let Some(limits) = &self.device_limits {
+            let exceeds = |value: Option<u64>, limit: u64| value.is_some_and(|v| v > limit);
+            if exceeds(options.shared_memory_bytes, limits.max_shared_memory) {
+                return Err("TuningOptions: shared_memory_bytes exceeds the device limit");
+            }
+            if exceeds(options.max_threads, limits.max_workgroup_invocations)
+                || exceeds(options.aim_threads, limits.max_workgroup_invocations)
+            {
+                return Err("TuningOptions: thread count exceeds the device limit");
+            }
+        }
+        let config = &mut self.config;
+        config.coalescedMemory = options.coalesced_memory.unwrap_or(0);
+        config.aimThreads = options.aim_threads.unwrap_or(0);
+        config.numSharedMemoryBanks = options.shared_memory_banks.unwrap_or(0);
+        config.registerBoost = options.register_boost.unwrap_or(0);
+        config.registerBoost4Step = options.register_boost_four_step.unwrap_or(0);
+        config.performBandwidthBoost = match options.bandwidth_boost {
+            None => 0,
+            Some(0) => -1,
+            Some(factor) => factor as i64,
+        };
+        config.useLUT = lut_flag(options.lut);
+        config.useLUT_4step = lut_flag(options.lut_four_step);
+        config.disableReorderFourStep = options.disable_reorder_four_step as u64;
+        config.swapTo3Stage4Step = options.three_upload_log2.map_or(0, u64::from);
+        config.groupedBatch = options.grouped_batch;
+        config.maxThreadsNum = options.max_threads.unwrap_or(0);
+        let detected_shared_memory = self
+            .device_limits
+            .map_or(0, |limits| limits.max_shared_memory);
+        config.sharedMemorySize = options
+            .shared_memory_bytes
+            .unwrap_or(detected_shared_memory);
+        Ok(())
+    }
+}
+
+/// VkFFT's tri-state LUT switches: 0 lets it decide, -1 forces them off.
+fn lut_flag(value: Option<bool>) -> i64 {
+    match value {
+        None => 0,
+        Some(true) => 1,
+        Some(false) => -1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn applies_and_resets_overrides() {
+        let mut fft = VkFft::new();
+        let options = TuningOptions {
+            coalesced_memory: Some(128),
+            bandwidth_boost: Some(0),
+            lut: Some(false),
+            grouped_batch: [4, 0, 0, 0],
+            ..Default::default()
+        };
+        fft.set_tuning(&options).unwrap();
+        assert_eq!(fft.config.coalescedMemory, 128);
+        assert_eq!(fft.config.performBandwidthBoost, -1);
+        assert_eq!(fft.config.useLUT, -1);
+        assert_eq!(fft.config.groupedBatch, [4, 0, 0, 0]);
+
+        fft.set_tuning(&TuningOptions::default()).unwrap();
+        assert_eq!(fft.config.coalescedMemory, 0);
+        assert_eq!(fft.config.performBandwidthBoost, 0);
+        assert_eq!(fft.config.useLUT, 0);
+
+        let invalid = TuningOptions {
+            shared_memory_banks: Some(24),
+            ..Default::default()
+        };
+        assert!(fft.set_tuning(&invalid).is_err());
+        assert!(TuningOptions {
+            three_upload_log2: Some(12),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn fields_round_trip() {
+        let options = TuningOptions {
+            aim_threads: Some(256),
+            register_boost: Some(2),
+            lut_four_step: Some(true),
+            disable_reorder_four_step: true,
+            grouped_batch: [0, 8, 0, 0],
+            ..Default::default()
+        };
+        let text = options.to_string();
+        assert_eq!(
+            text,
+            "aim_threads=256 register_boost=2 lut_four_step=1 \
+             disable_reorder_four_step=1 grouped_batch=0,8,0,0"
+        );
+
+        let mut parsed = TuningOptions::default();
+        for (name, value) in text.split(' ').filter_map(|field| field.split_once('=')) {
+            assert!(parsed.parse_field(name, value).unwrap());
+        }
+        assert_eq!(parsed, options);
+        assert!(!parsed.parse_field("vendor", "1").unwrap());
+        assert!(parsed.parse_field("lut", "yes").is_err());
+        assert_eq!(TuningOptions::default().to_string(), "defaults");
+    }
+}
@@ -0,0 +1,70 @@
+//! The VkFFT release compiled into the shim.
+//!
+//! VkFFT's configuration fields and serialized plans change between
+//! releases, so the version is recorded at build time and checked wherever
+//! data produced by another build can come back in.
+
+use core::fmt;
+
+use crate::ffi;
+
+/// A VkFFT release as encoded by `VkFFTGetVersion` (X.XX.XX).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    /// Decode `VkFFTGetVersion`'s value, e.g. 10304 for 1.3.4.
+    pub const fn from_raw(raw: u32) -> Self {
+        Self {
+            major: raw / 10000,
+            minor: raw / 100 % 100,
+            patch: raw % 100,
+        }
+    }
+
+    /// The encoding used by `VkFFTGetVersion`.
+    pub const fn raw(self) -> u32 {
+        self.major * 10000 + self.minor * 100 + self.patch
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Version of the VkFFT headers the shim was compiled from.
+pub fn version() -> Version {
+    Version::from_raw(unsafe { ffi::vkfft_get_version() } as u32)
+}
+
+/// Commit of the VkFFT checkout the crate was built from, or `None` when the
+/// headers did not come from a git checkout (e.g. `VKFFT_INCLUDE_DIR`).
+pub const VKFFT_COMMIT: Option<&str> = option_env!("VKFFT_COMMIT");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn decodes_the_vkfft_encoding() {
+        let version = Version::from_raw(10304);
+        assert_eq!(
+            version,
+            Version {
+                major: 1,
+                minor: 3,
+                patch: 4
+            }
+        );
+        assert_eq!(version.raw(), 10304);
+        assert_eq!(version.to_string(), "1.3.4");
+        assert!(Version::from_raw(10210) < version);
+    }
+}
@@ -1,268 +1,147 @@
 use alloc::vec::Vec;
 use ash::vk;
 use core::ffi::c_void;
-use core::fmt;
 
+use crate::device_limits::DeviceLimits;
 use crate::ffi;
+use crate::normalization::Normalization;
+use crate::profiling::GpuProfiler;
+pub use crate::result::{vkfft_result_to_str, VkFftError};
+use crate::trace;
+use crate::version::Version;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub struct VkFftError {
-    pub code: ffi::VkFFTResult,
-    pub message: &'static str,
+fn vkfft_ok(r: ffi::VkFFTResult) -> bool {
+    // Common bindgen output: a constant VKFFT_SUCCESS: u32
+    // or a direct enum variant. Use whichever compiles.
+    r == ffi::VkFFTResult::VKFFT_SUCCESS
 }
 
-impl VkFftError {
-    pub fn from_result(code: ffi::VkFFTResult) -> Self {
-        Self {
-            code,
-            message: vkfft_result_to_str(code),
+/// Per-axis messages for [`VkFft::validate_omitted_axes`], for the first four
+/// axes; builds with a larger `VKFFT_MAX_FFT_DIMENSIONS` fall back to
+/// [`omit_axis_message`]'s generic text for the others.
+const OMIT_BEYOND_FFTDIM: [&str; 4] = [
+    "omitDimension: axis 0 is omitted but is not below FFTdim",
+    "omitDimension: axis 1 is omitted but is not below FFTdim",
+    "omitDimension: axis 2 is omitted but is not below FFTdim",
+    "omitDimension: axis 3 is omitted but is not below FFTdim",
+];
+
+const OMIT_WITH_CONVOLUTION: [&str; 4] = [
+    "omitDimension: axis 0 cannot be omitted when performConvolution is set",
+    "omitDimension: axis 1 cannot be omitted when performConvolution is set",
+    "omitDimension: axis 2 cannot be omitted when performConvolution is set",
+    "omitDimension: axis 3 cannot be omitted when performConvolution is set",
+];
+
+/// Sizes of buffers holding one array: positive, within `max_range`, and,
+/// for split buffers, blocks of the first buffer's size holding whole
+/// elements with a possibly shorter last block.
+fn check_buffer_sizes(
+    sizes: &[u64],
+    element_bytes: u64,
+    max_range: Option<u64>,
+) -> Result<(), &'static str> {
+    let Some((&first, rest)) = sizes.split_first() else {
+        return Err("bufferSize: at least one buffer size is required");
+    };
+    if sizes.contains(&0) {
+        return Err("bufferSize: buffer sizes must be positive");
+    }
+    if max_range.is_some_and(|range| sizes.iter().any(|&size| size > range)) {
+        return Err("bufferSize: a buffer exceeds maxStorageBufferRange; split it further");
+    }
+    if let Some((&last, middle)) = rest.split_last() {
+        if first % element_bytes != 0 {
+            return Err("bufferSize: split buffers must hold whole complex elements");
+        }
+        if middle.iter().any(|&size| size != first) {
+            return Err(
+                "bufferSize: every split buffer but the last must have the first one's size",
+            );
+        }
+        if last > first {
+            return Err("bufferSize: the last split buffer must not be larger than the first");
         }
     }
+    Ok(())
 }
 
-impl fmt::Display for VkFftError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.message)
+fn buffer_size_error(message: &'static str) -> VkFftError {
+    VkFftError {
+        code: ffi::VkFFTResult::VKFFT_ERROR_EMPTY_bufferSize,
+        message,
     }
 }
 
-pub fn vkfft_result_to_str(result: ffi::VkFFTResult) -> &'static str {
-    match result {
-        ffi::VkFFTResult::VKFFT_SUCCESS => "VKFFT_SUCCESS",
-        ffi::VkFFTResult::VKFFT_ERROR_MALLOC_FAILED => "VKFFT_ERROR_MALLOC_FAILED",
-        ffi::VkFFTResult::VKFFT_ERROR_INSUFFICIENT_CODE_BUFFER => {
-            "VKFFT_ERROR_INSUFFICIENT_CODE_BUFFER"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_INSUFFICIENT_TEMP_BUFFER => {
-            "VKFFT_ERROR_INSUFFICIENT_TEMP_BUFFER"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_PLAN_NOT_INITIALIZED => "VKFFT_ERROR_PLAN_NOT_INITIALIZED",
-        ffi::VkFFTResult::VKFFT_ERROR_NULL_TEMP_PASSED => "VKFFT_ERROR_NULL_TEMP_PASSED",
-        ffi::VkFFTResult::VKFFT_ERROR_MATH_FAILED => "VKFFT_ERROR_MATH_FAILED",
-        ffi::VkFFTResult::VKFFT_ERROR_FFTdim_GT_MAX_FFT_DIMENSIONS => {
-            "VKFFT_ERROR_FFTdim_GT_MAX_FFT_DIMENSIONS"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_NONZERO_APP_INITIALIZATION => {
-            "VKFFT_ERROR_NONZERO_APP_INITIALIZATION"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_INVALID_PHYSICAL_DEVICE => {
-            "VKFFT_ERROR_INVALID_PHYSICAL_DEVICE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_INVALID_DEVICE => "VKFFT_ERROR_INVALID_DEVICE",
-        ffi::VkFFTResult::VKFFT_ERROR_INVALID_QUEUE => "VKFFT_ERROR_INVALID_QUEUE",
-        ffi::VkFFTResult::VKFFT_ERROR_INVALID_COMMAND_POOL => "VKFFT_ERROR_INVALID_COMMAND_POOL",
-        ffi::VkFFTResult::VKFFT_ERROR_INVALID_FENCE => "VKFFT_ERROR_INVALID_FENCE",
-        ffi::VkFFTResult::VKFFT_ERROR_ONLY_FORWARD_FFT_INITIALIZED => {
-            "VKFFT_ERROR_ONLY_FORWARD_FFT_INITIALIZED"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_ONLY_INVERSE_FFT_INITIALIZED => {
-            "VKFFT_ERROR_ONLY_INVERSE_FFT_INITIALIZED"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_INVALID_CONTEXT => "VKFFT_ERROR_INVALID_CONTEXT",
-        ffi::VkFFTResult::VKFFT_ERROR_INVALID_PLATFORM => "VKFFT_ERROR_INVALID_PLATFORM",
-        ffi::VkFFTResult::VKFFT_ERROR_ENABLED_saveApplicationToString => {
-            "VKFFT_ERROR_ENABLED_saveApplicationToString"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_FILE => "VKFFT_ERROR_EMPTY_FILE",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_FFTdim => "VKFFT_ERROR_EMPTY_FFTdim",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_size => "VKFFT_ERROR_EMPTY_size",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_bufferSize => "VKFFT_ERROR_EMPTY_bufferSize",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_buffer => "VKFFT_ERROR_EMPTY_buffer",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_tempBufferSize => "VKFFT_ERROR_EMPTY_tempBufferSize",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_tempBuffer => "VKFFT_ERROR_EMPTY_tempBuffer",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_inputBufferSize => "VKFFT_ERROR_EMPTY_inputBufferSize",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_inputBuffer => "VKFFT_ERROR_EMPTY_inputBuffer",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_outputBufferSize => {
-            "VKFFT_ERROR_EMPTY_outputBufferSize"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_outputBuffer => "VKFFT_ERROR_EMPTY_outputBuffer",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_kernelSize => "VKFFT_ERROR_EMPTY_kernelSize",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_kernel => "VKFFT_ERROR_EMPTY_kernel",
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_applicationString => {
-            "VKFFT_ERROR_EMPTY_applicationString"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_useCustomBluesteinPaddingPattern_arrays => {
-            "VKFFT_ERROR_EMPTY_useCustomBluesteinPaddingPattern_arrays"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_EMPTY_app => "VKFFT_ERROR_EMPTY_app",
-        ffi::VkFFTResult::VKFFT_ERROR_INVALID_user_tempBuffer_too_small => {
-            "VKFFT_ERROR_INVALID_user_tempBuffer_too_small"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_RADIX => "VKFFT_ERROR_UNSUPPORTED_RADIX",
-        ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH => {
-            "VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH_R2C => {
-            "VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH_R2C"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH_R2R => {
-            "VKFFT_ERROR_UNSUPPORTED_FFT_LENGTH_R2R"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_OMIT => "VKFFT_ERROR_UNSUPPORTED_FFT_OMIT",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_ALLOCATE => "VKFFT_ERROR_FAILED_TO_ALLOCATE",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_MAP_MEMORY => "VKFFT_ERROR_FAILED_TO_MAP_MEMORY",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_ALLOCATE_COMMAND_BUFFERS => {
-            "VKFFT_ERROR_FAILED_TO_ALLOCATE_COMMAND_BUFFERS"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_BEGIN_COMMAND_BUFFER => {
-            "VKFFT_ERROR_FAILED_TO_BEGIN_COMMAND_BUFFER"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_END_COMMAND_BUFFER => {
-            "VKFFT_ERROR_FAILED_TO_END_COMMAND_BUFFER"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_SUBMIT_QUEUE => {
-            "VKFFT_ERROR_FAILED_TO_SUBMIT_QUEUE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_WAIT_FOR_FENCES => {
-            "VKFFT_ERROR_FAILED_TO_WAIT_FOR_FENCES"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_RESET_FENCES => {
-            "VKFFT_ERROR_FAILED_TO_RESET_FENCES"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_DESCRIPTOR_POOL => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_DESCRIPTOR_POOL"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_DESCRIPTOR_SET_LAYOUT => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_DESCRIPTOR_SET_LAYOUT"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_ALLOCATE_DESCRIPTOR_SETS => {
-            "VKFFT_ERROR_FAILED_TO_ALLOCATE_DESCRIPTOR_SETS"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_PIPELINE_LAYOUT => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_PIPELINE_LAYOUT"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_SHADER_PREPROCESS => {
-            "VKFFT_ERROR_FAILED_SHADER_PREPROCESS"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_SHADER_PARSE => "VKFFT_ERROR_FAILED_SHADER_PARSE",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_SHADER_LINK => "VKFFT_ERROR_FAILED_SHADER_LINK",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_SPIRV_GENERATE => "VKFFT_ERROR_FAILED_SPIRV_GENERATE",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_SHADER_MODULE => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_SHADER_MODULE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_INSTANCE => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_INSTANCE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_SETUP_DEBUG_MESSENGER => {
-            "VKFFT_ERROR_FAILED_TO_SETUP_DEBUG_MESSENGER"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_FIND_PHYSICAL_DEVICE => {
-            "VKFFT_ERROR_FAILED_TO_FIND_PHYSICAL_DEVICE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_DEVICE => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_DEVICE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_FENCE => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_FENCE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_COMMAND_POOL => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_COMMAND_POOL"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_BUFFER => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_BUFFER"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_ALLOCATE_MEMORY => {
-            "VKFFT_ERROR_FAILED_TO_ALLOCATE_MEMORY"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_BIND_BUFFER_MEMORY => {
-            "VKFFT_ERROR_FAILED_TO_BIND_BUFFER_MEMORY"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_FIND_MEMORY => "VKFFT_ERROR_FAILED_TO_FIND_MEMORY",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_SYNCHRONIZE => "VKFFT_ERROR_FAILED_TO_SYNCHRONIZE",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_COPY => "VKFFT_ERROR_FAILED_TO_COPY",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_PROGRAM => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_PROGRAM"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_COMPILE_PROGRAM => {
-            "VKFFT_ERROR_FAILED_TO_COMPILE_PROGRAM"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_GET_CODE_SIZE => {
-            "VKFFT_ERROR_FAILED_TO_GET_CODE_SIZE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_GET_CODE => "VKFFT_ERROR_FAILED_TO_GET_CODE",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_DESTROY_PROGRAM => {
-            "VKFFT_ERROR_FAILED_TO_DESTROY_PROGRAM"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_LOAD_MODULE => "VKFFT_ERROR_FAILED_TO_LOAD_MODULE",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_GET_FUNCTION => {
-            "VKFFT_ERROR_FAILED_TO_GET_FUNCTION"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_SET_DYNAMIC_SHARED_MEMORY => {
-            "VKFFT_ERROR_FAILED_TO_SET_DYNAMIC_SHARED_MEMORY"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_MODULE_GET_GLOBAL => {
-            "VKFFT_ERROR_FAILED_TO_MODULE_GET_GLOBAL"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_LAUNCH_KERNEL => {
-            "VKFFT_ERROR_FAILED_TO_LAUNCH_KERNEL"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_EVENT_RECORD => {
-            "VKFFT_ERROR_FAILED_TO_EVENT_RECORD"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_ADD_NAME_EXPRESSION => {
-            "VKFFT_ERROR_FAILED_TO_ADD_NAME_EXPRESSION"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_INITIALIZE => "VKFFT_ERROR_FAILED_TO_INITIALIZE",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_SET_DEVICE_ID => {
-            "VKFFT_ERROR_FAILED_TO_SET_DEVICE_ID"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_GET_DEVICE => "VKFFT_ERROR_FAILED_TO_GET_DEVICE",
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_CONTEXT => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_CONTEXT"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_PIPELINE => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_PIPELINE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_SET_KERNEL_ARG => {
-            "VKFFT_ERROR_FAILED_TO_SET_KERNEL_ARG"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_COMMAND_QUEUE => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_COMMAND_QUEUE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_RELEASE_COMMAND_QUEUE => {
-            "VKFFT_ERROR_FAILED_TO_RELEASE_COMMAND_QUEUE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_ENUMERATE_DEVICES => {
-            "VKFFT_ERROR_FAILED_TO_ENUMERATE_DEVICES"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_GET_ATTRIBUTE => {
-            "VKFFT_ERROR_FAILED_TO_GET_ATTRIBUTE"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_EVENT => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_EVENT"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_CREATE_COMMAND_LIST => {
-            "VKFFT_ERROR_FAILED_TO_CREATE_COMMAND_LIST"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_DESTROY_COMMAND_LIST => {
-            "VKFFT_ERROR_FAILED_TO_DESTROY_COMMAND_LIST"
-        }
-        ffi::VkFFTResult::VKFFT_ERROR_FAILED_TO_SUBMIT_BARRIER => {
-            "VKFFT_ERROR_FAILED_TO_SUBMIT_BARRIER"
-        }
-        _ => "Unknown VkFFT error",
+/// `table[axis]`, or `fallback` for axes the table has no entry for.
+fn omit_axis_message(table: &[&'static str], axis: usize, fallback: &'static str) -> &'static str {
+    table.get(axis).copied().unwrap_or(fallback)
+}
+
+fn omit_error(message: &'static str) -> VkFftError {
+    VkFftError {
+        code: ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_OMIT,
+        message,
     }
 }
 
-fn vkfft_ok(r: ffi::VkFFTResult) -> bool {
-    // Common bindgen output: a constant VKFFT_SUCCESS: u32
-    // or a direct enum variant. Use whichever compiles.
-    r == ffi::VkFFTResult::VKFFT_SUCCESS
+fn vkfft_check(r: ffi::VkFFTResult) -> Result<(), VkFftError> {
+    if vkfft_ok(r) {
+        Ok(())
+    } else {
+        Err(VkFftError::from_result(r))
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum VkfftError {
     Vkfft(ffi::VkFFTResult),
+    /// A Vulkan call made by the wrapper itself (not by VkFFT) failed.
+    Vulkan(vk::Result),
+    /// The handle does not belong to the Vulkan backend.
+    UnsupportedBackend,
+    /// An argument was rejected by the wrapper before reaching VkFFT.
+    InvalidArgument(&'static str),
+    /// The configuration would be rejected by VkFFT; carries the result code
+    /// VkFFT would return and an explanation.
+    Unsupported(VkFftError),
+    /// No physical device with a compute queue matched the selector.
+    NoDevice,
+    /// A serialized plan was produced by a different VkFFT release.
+    PlanVersion {
+        plan: Version,
+        library: Version,
+    },
+    /// The plan needs shader compilation, but glslang could not be loaded
+    /// (`dlopen-glslang`) or the crate was built with `precompiled-only`.
+    CompilerUnavailable,
 }
 
 impl core::fmt::Display for VkfftError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Vkfft(res) => write!(f, "VkFFT error: {:?}", res),
+            Self::Vulkan(res) => write!(f, "Vulkan error: {:?}", res),
+            Self::UnsupportedBackend => write!(f, "handle is not backed by Vulkan"),
+            Self::InvalidArgument(msg) => write!(f, "invalid argument: {}", msg),
+            Self::Unsupported(err) => {
+                write!(f, "{}: {}", vkfft_result_to_str(err.code), err.message)
+            }
+            Self::NoDevice => write!(f, "no matching Vulkan device with a compute queue"),
+            Self::PlanVersion { plan, library } => write!(
+                f,
+                "serialized plan was produced by VkFFT {}, this build uses VkFFT {}",
+                plan, library
+            ),
+            Self::CompilerUnavailable => write!(
+                f,
+                "no shader compiler available; load a saved plan or make glslang loadable"
+            ),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for VkfftError {}
 
 impl From<ffi::VkFFTResult> for VkfftError {
@@ -271,6 +150,24 @@ impl From<ffi::VkFFTResult> for VkfftError {
     }
 }
 
+impl From<VkFftError> for VkfftError {
+    fn from(value: VkFftError) -> Self {
+        Self::Vkfft(value.code)
+    }
+}
+
+impl From<&'static str> for VkfftError {
+    fn from(value: &'static str) -> Self {
+        Self::InvalidArgument(value)
+    }
+}
+
+impl From<vk::Result> for VkfftError {
+    fn from(value: vk::Result) -> Self {
+        Self::Vulkan(value)
+    }
+}
+
 /// Thin wrapper around VkFFTApplication lifetime.
 ///
 /// VkFFT is C-style; most functions return an error code.
@@ -279,21 +176,32 @@ impl From<ffi::VkFFTResult> for VkfftError {
 /// - initialize (create VkFFT plan)
 /// - append (record FFT dispatch into an existing command buffer)
 pub struct VkFft {
-    app: ffi::VkFFTApplication,
-    config: ffi::VkFFTConfiguration,
-    initialized: bool,
-    buffer_sizes: [u64; 1],
+    pub(crate) app: ffi::VkFFTApplication,
+    pub(crate) config: ffi::VkFFTConfiguration,
+    pub(crate) initialized: bool,
 
     // Keep Vulkan handles alive because VkFFTConfiguration stores pointers to them.
-    phys: vk::PhysicalDevice,
+    pub(crate) phys: vk::PhysicalDevice,
     dev: vk::Device,
     queue: vk::Queue,
     command_pool: vk::CommandPool,
-    buffers: Vec<vk::Buffer>,
-    output_buffers: Vec<vk::Buffer>,
+    fence: vk::Fence,
+    pub(crate) buffers: Vec<vk::Buffer>,
+    input_buffers: Vec<vk::Buffer>,
+    pub(crate) output_buffers: Vec<vk::Buffer>,
 
-    buffer_sizes: Vec<u64>,
+    pub(crate) buffer_sizes: Vec<u64>,
     temp_buffer_sizes: Vec<u64>,
+    input_buffer_sizes: Vec<u64>,
+    pub(crate) output_buffer_sizes: Vec<u64>,
+    pub(crate) bluestein_primes: Vec<u64>,
+    pub(crate) bluestein_padded: Vec<u64>,
+    pub(crate) device_limits: Option<DeviceLimits>,
+    // Plan loaded by `load_plan`; VkFFT reads it during `initialize`.
+    pub(crate) plan_string: Vec<u8>,
+
+    pub(crate) normalization: Normalization,
+    pub(crate) profiler: Option<GpuProfiler>,
 }
 
 impl VkFft {
@@ -309,17 +217,26 @@ impl VkFft {
             app,
             config,
             initialized: false,
-            buffer_sizes: [0],
 
             phys: vk::PhysicalDevice::null(),
             dev: vk::Device::null(),
             queue: vk::Queue::null(),
             command_pool: vk::CommandPool::null(),
+            fence: vk::Fence::null(),
             buffers: Vec::new(),
+            input_buffers: Vec::new(),
             output_buffers: Vec::new(),
             buffer_sizes: Vec::new(),
             temp_buffer_sizes: Vec::new(),
+            input_buffer_sizes: Vec::new(),
+            output_buffer_sizes: Vec::new(),
+            bluestein_primes: Vec::new(),
+            bluestein_padded: Vec::new(),
+            device_limits: None,
+            plan_string: Vec::new(),
 
+            normalization: Normalization::None,
+            profiler: None,
         }
     }
 
@@ -330,6 +247,9 @@ impl VkFft {
     ///
     /// You must also provide FFT dimensions and buffer pointers separately using `configure_*`
     /// before calling `initialize` (or extend this helper to do it all at once).
+    ///
+    /// [`VkFft::configure_vulkan_with_instance`] additionally detects the
+    /// device's limits and precision support.
     pub fn configure_vulkan(
         &mut self,
         physical_device: vk::PhysicalDevice,
@@ -356,6 +276,16 @@ impl VkFft {
         self.config.commandPool = (&mut self.command_pool as *mut vk::CommandPool).cast();
     }
 
+    /// Provide the fence VkFFT waits on while it uploads lookup tables and
+    /// Bluestein kernels during `initialize`.
+    ///
+    /// The fence must be unsignaled and must not be in use by other
+    /// submissions while `initialize` runs.
+    pub fn configure_fence(&mut self, fence: vk::Fence) {
+        self.fence = fence;
+        self.config.fence = (&mut self.fence as *mut vk::Fence).cast();
+    }
+
     /// Configure FFT sizes.
     ///
     /// Example: 2D FFT => pass [width, height] and set `dim = 2`.
@@ -383,10 +313,13 @@ impl VkFft {
     /// generated bindings for. Provide at least one input buffer; optionally pass a matching slice
     /// of output buffers to enable out-of-place execution.
     ///
+    /// Several input buffers are VkFFT's split-buffer mode: one array spread over the buffers in
+    /// order, not one buffer per batch. Give their sizes with [`VkFft::set_buffer_sizes`], or use
+    /// [`VkFft::configure_split_buffer`] to set and check both at once.
+    ///
     /// # Errors
     ///
-    /// Returns an error if any provided slice is empty, if input/output slice lengths differ, or if
-    /// the slice length conflicts with an explicitly configured `numberBatches`.
+    /// Returns an error if any provided slice is empty or if input/output slice lengths differ.
     ///
     /// # Examples
     ///
@@ -432,18 +365,16 @@ impl VkFft {
             }
         }
 
-        if self.config.numberBatches > 1
-            && input_buffers.len() > 1
-            && input_buffers.len() as u64 != self.config.numberBatches
-        {
-            return Err("configure_buffers: buffer count must match configured numberBatches");
-        }
-
         self.buffers.clear();
         self.buffers.extend_from_slice(input_buffers);
         self.config.bufferNum = self.buffers.len() as u64;
         self.config.buffer = self.buffers.as_mut_ptr().cast();
 
+        self.input_buffers.clear();
+        self.input_buffer_sizes.clear();
+        self.output_buffer_sizes.clear();
+        self.config.inputBufferSize = core::ptr::null_mut();
+        self.config.outputBufferSize = core::ptr::null_mut();
         if let Some(out) = output_buffers {
             self.output_buffers.clear();
             self.output_buffers.extend_from_slice(out);
@@ -462,6 +393,152 @@ impl VkFft {
         Ok(())
     }
 
+    /// Spread one in-place array over several buffers (VkFFT's split-buffer
+    /// mode), e.g. when it is larger than the device's
+    /// `maxStorageBufferRange`. `sizes[i]` is the size of `buffers[i]` in
+    /// bytes.
+    ///
+    /// VkFFT addresses the buffers as blocks of the first buffer's size, so
+    /// every buffer but the last must have that size and hold whole complex
+    /// elements; the last may be shorter. Configure the precision first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the slices are empty or differ in length, or if
+    /// the sizes break the rules above or exceed the detected
+    /// `maxStorageBufferRange`.
+    pub fn configure_split_buffer(
+        &mut self,
+        buffers: &[vk::Buffer],
+        sizes: &[vk::DeviceSize],
+    ) -> Result<(), &'static str> {
+        if buffers.len() != sizes.len() {
+            return Err("configure_split_buffer: one size per buffer is required");
+        }
+        check_buffer_sizes(
+            sizes,
+            self.storage_complex_bytes(),
+            self.max_storage_buffer_range(),
+        )?;
+        self.configure_buffers(buffers, None)?;
+        self.set_buffer_sizes(sizes);
+        Ok(())
+    }
+
+    /// Transform out of place through a separate working buffer: VkFFT reads
+    /// `input`, transforms in `buffer` and writes `output`, with
+    /// `isInputFormatted` and `isOutputFormatted` set. Sizes are in bytes.
+    ///
+    /// This is the only layout VkFFT supports for fp16 storage computed in
+    /// fp32 (`halfPrecisionMemoryOnly`): `input` and `output` hold fp16
+    /// values and `buffer` the same number of fp32 values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any size is zero.
+    pub fn configure_formatted_buffers(
+        &mut self,
+        input: vk::Buffer,
+        input_size: vk::DeviceSize,
+        buffer: vk::Buffer,
+        buffer_size: vk::DeviceSize,
+        output: vk::Buffer,
+        output_size: vk::DeviceSize,
+    ) -> Result<(), &'static str> {
+        if input_size == 0 || buffer_size == 0 || output_size == 0 {
+            return Err("configure_formatted_buffers: buffer sizes must be positive");
+        }
+        self.configure_buffers(&[buffer], None)?;
+        self.set_buffer_sizes(&[buffer_size]);
+
+        self.input_buffers.clear();
+        self.input_buffers.push(input);
+        self.output_buffers.clear();
+        self.output_buffers.push(output);
+        self.input_buffer_sizes.clear();
+        self.input_buffer_sizes.push(input_size);
+        self.output_buffer_sizes.clear();
+        self.output_buffer_sizes.push(output_size);
+        self.config.inputBufferNum = 1;
+        self.config.outputBufferNum = 1;
+        self.config.inputBuffer = self.input_buffers.as_mut_ptr().cast();
+        self.config.outputBuffer = self.output_buffers.as_mut_ptr().cast();
+        self.config.inputBufferSize = self.input_buffer_sizes.as_mut_ptr();
+        self.config.outputBufferSize = self.output_buffer_sizes.as_mut_ptr();
+        self.set_layout_flags(true, true);
+        Ok(())
+    }
+
+    /// Check that fp16 storage computed in fp32 uses the out-of-place layout
+    /// of [`VkFft::configure_formatted_buffers`]; VkFFT does not support it
+    /// in place.
+    pub fn validate_half_precision_layout(&self) -> Result<(), VkFftError> {
+        let config = &self.config;
+        if config.halfPrecisionMemoryOnly != 0
+            && (config.isInputFormatted == 0
+                || config.isOutputFormatted == 0
+                || config.inputBuffer.is_null()
+                || config.outputBuffer.is_null())
+        {
+            return Err(VkFftError {
+                code: ffi::VkFFTResult::VKFFT_ERROR_EMPTY_inputBuffer,
+                message:
+                    "halfPrecisionMemoryOnly: fp16 storage with fp32 compute only works out of \
+                          place; use configure_formatted_buffers",
+            });
+        }
+        Ok(())
+    }
+
+    /// Check the configured buffer sizes against `bufferNum` and each other.
+    ///
+    /// Applies the rules of [`VkFft::configure_split_buffer`] to sizes set
+    /// through [`VkFft::set_buffer_sizes`]. `initialize` runs this check.
+    pub fn validate_buffer_sizes(&self) -> Result<(), VkFftError> {
+        if self.buffer_sizes.is_empty() {
+            return Ok(());
+        }
+        let buffers = self.config.bufferNum.max(1) as usize;
+        if self.buffer_sizes.len() != buffers {
+            return Err(buffer_size_error(
+                "bufferSize: one size per buffer is required",
+            ));
+        }
+        check_buffer_sizes(
+            &self.buffer_sizes,
+            self.storage_complex_bytes(),
+            self.max_storage_buffer_range(),
+        )
+        .map_err(buffer_size_error)
+    }
+
+    /// Bytes of one complex element as stored in the buffers. With fp16
+    /// storage computed in fp32 the buffers VkFFT transforms in hold fp32
+    /// values; only the formatted input and output are fp16.
+    fn storage_complex_bytes(&self) -> u64 {
+        let config = &self.config;
+        if config.quadDoubleDoublePrecision != 0 {
+            32
+        } else if config.quadDoubleDoublePrecisionDoubleMemory != 0 {
+            16
+        } else if config.halfPrecisionMemoryOnly != 0 {
+            8
+        } else if config.halfPrecision != 0 {
+            4
+        } else if config.doublePrecision != 0 && config.doublePrecisionFloatMemory == 0 {
+            16
+        } else {
+            8
+        }
+    }
+
+    fn max_storage_buffer_range(&self) -> Option<u64> {
+        self.device_limits
+            .as_ref()
+            .map(|limits| limits.max_storage_buffer_range)
+            .filter(|&range| range != 0)
+    }
+
     /// Enable or disable double-precision FFT kernels.
     ///
     /// Default VkFFT behavior leaves `doublePrecision` at 0, selecting
@@ -488,6 +565,27 @@ impl VkFft {
         self.config.halfPrecision = enabled as u64;
     }
 
+    /// Enable or disable real-to-complex transforms.
+    ///
+    /// With `performR2C` set, the forward transform reads `size[0]` real
+    /// values per row and writes `size[0] / 2 + 1` complex values. In-place
+    /// buffers must hold the padded complex layout.
+    pub fn set_r2c(&mut self, enabled: bool) {
+        self.config.performR2C = enabled as u64;
+    }
+
+    /// Select a real-to-real discrete cosine transform.
+    ///
+    /// `kind` is the DCT type (1 to 4); 0 disables DCT and restores complex
+    /// transforms.
+    pub fn set_dct(&mut self, kind: u64) -> Result<(), &'static str> {
+        if kind > 4 {
+            return Err("set_dct: DCT type must be between 1 and 4");
+        }
+        self.config.performDCT = kind;
+        Ok(())
+    }
+
     /// Configure the number of batched FFTs.
     ///
     /// By default VkFFT uses a single batch when `numberBatches` is 0.
@@ -527,6 +625,88 @@ impl VkFft {
         }
     }
 
+    /// Choose whether `axis` is transformed or skipped.
+    ///
+    /// Axes follow VkFFT's order (axis 0 is the contiguous one). Skipped axes
+    /// are set through `omitDimension` and behave like batch dimensions, which
+    /// allows e.g. batched 2D transforms over the slices of a 3D volume.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VKFFT_ERROR_UNSUPPORTED_FFT_OMIT` if `axis` is outside the
+    /// range VkFFT can describe.
+    pub fn set_axis_transform(&mut self, axis: usize, transform: bool) -> Result<(), VkFftError> {
+        if axis >= self.config.omitDimension.len() {
+            return Err(omit_error(
+                "omitDimension: axis index exceeds VKFFT_MAX_FFT_DIMENSIONS",
+            ));
+        }
+        self.config.omitDimension[axis] = (!transform) as u64;
+        Ok(())
+    }
+
+    /// Transform exactly the listed axes and skip all other axes of the plan.
+    ///
+    /// Call after [`VkFft::configure_dimensions`]: only axes below `FFTdim`
+    /// are skipped, and the flags of the axes beyond it are cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VKFFT_ERROR_UNSUPPORTED_FFT_OMIT` if a listed axis is not
+    /// below `FFTdim`.
+    pub fn set_transformed_axes(&mut self, axes: &[usize]) -> Result<(), VkFftError> {
+        let dims = self.config.omitDimension.len();
+        let fft_dims = (self.config.FFTdim as usize).min(dims);
+        if axes.iter().any(|&axis| axis >= fft_dims) {
+            return Err(omit_error(
+                "omitDimension: transformed axes must be below FFTdim",
+            ));
+        }
+        for axis in 0..dims {
+            self.config.omitDimension[axis] = (axis < fft_dims && !axes.contains(&axis)) as u64;
+        }
+        Ok(())
+    }
+
+    /// Check the `omitDimension` settings against the rest of the plan.
+    ///
+    /// VkFFT cannot skip the first axis of an R2C transform, does not support
+    /// skipping axes in convolutions, and needs at least one transformed axis.
+    /// `initialize` runs this check so the offending axis can be named instead
+    /// of surfacing a bare `VKFFT_ERROR_UNSUPPORTED_FFT_OMIT`.
+    pub fn validate_omitted_axes(&self) -> Result<(), VkFftError> {
+        let dims = (self.config.FFTdim as usize).min(self.config.omitDimension.len());
+        let omitted = |axis: usize| self.config.omitDimension[axis] != 0;
+
+        if let Some(axis) = (dims..self.config.omitDimension.len()).find(|&axis| omitted(axis)) {
+            return Err(omit_error(omit_axis_message(
+                &OMIT_BEYOND_FFTDIM,
+                axis,
+                "omitDimension: an axis that is not below FFTdim is omitted",
+            )));
+        }
+        if (0..dims).all(omitted) {
+            return Err(omit_error(
+                "omitDimension: every axis is omitted, nothing to transform",
+            ));
+        }
+        if self.config.performR2C != 0 && omitted(0) {
+            return Err(omit_error(
+                "omitDimension: axis 0 cannot be omitted for R2C transforms",
+            ));
+        }
+        if self.config.performConvolution != 0 {
+            if let Some(axis) = (0..dims).find(|&axis| omitted(axis)) {
+                return Err(omit_error(omit_axis_message(
+                    &OMIT_WITH_CONVOLUTION,
+                    axis,
+                    "omitDimension: axes cannot be omitted when performConvolution is set",
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Declare whether input and output buffers are already formatted for VkFFT.
     ///
     /// VkFFT defaults to `isInputFormatted = 0` and `isOutputFormatted = 0`,
@@ -541,11 +721,13 @@ impl VkFft {
         self.config.isOutputFormatted = output_formatted as u64;
     }
 
-    /// Provide explicit sizes for the primary buffers in bytes.
+    /// Provide explicit sizes for the primary buffers in bytes, one per
+    /// buffer passed to [`VkFft::configure_buffers`].
     ///
     /// Defaults leave `bufferSize` as a null pointer, allowing VkFFT to
     /// infer sizes from dimensions. Supplying values is useful when VkFFT
-    /// should validate or adjust for exact buffer sizes.
+    /// should validate or adjust for exact buffer sizes. `initialize` checks
+    /// them with [`VkFft::validate_buffer_sizes`].
     ///
     /// # Safety
     /// Sizes must match the actual allocations referenced by `buffer` and
@@ -561,6 +743,22 @@ impl VkFft {
         };
     }
 
+    /// Provide the sizes of the output buffers passed to
+    /// [`VkFft::configure_buffers`] in bytes, one per buffer.
+    ///
+    /// VkFFT reads them from `outputBufferSize`; the scaling pass of
+    /// [`VkFft::append_normalized`] needs them to cover the output buffers.
+    /// [`VkFft::configure_formatted_buffers`] sets them itself.
+    pub fn set_output_buffer_sizes(&mut self, sizes: &[u64]) {
+        self.output_buffer_sizes.clear();
+        self.output_buffer_sizes.extend_from_slice(sizes);
+        self.config.outputBufferSize = if self.output_buffer_sizes.is_empty() {
+            core::ptr::null_mut()
+        } else {
+            self.output_buffer_sizes.as_mut_ptr()
+        };
+    }
+
     /// Provide explicit sizes for temporary buffers in bytes.
     ///
     /// Defaults leave `tempBufferSize` null, letting VkFFT pick temporary
@@ -581,11 +779,10 @@ impl VkFft {
         };
     }
 
-    /// Set the size of the buffer in bytes.
+    /// Set the size in bytes of a plan's only buffer. Split buffers need
+    /// one size each through [`VkFft::set_buffer_sizes`].
     pub fn configure_buffer_size(&mut self, buffer_size: vk::DeviceSize) {
-        self.buffer_sizes[0] = buffer_size;
-        self.config.bufferSize = self.buffer_sizes.as_mut_ptr();
-        self.config.bufferNum = 1;
+        self.set_buffer_sizes(&[buffer_size]);
     }
 
     /// Configure plan cache download to write the compiled plan into `blob_out`.
@@ -601,6 +798,7 @@ impl VkFft {
     }
 
     /// Configure plan cache upload by pointing VkFFT at a precompiled plan.
+    /// [`VkFft::load_plan`] also checks the VkFFT release and keeps a copy.
     ///
     /// # Safety
     /// `blob` must point to a valid plan string previously produced by
@@ -616,6 +814,10 @@ impl VkFft {
     /// The callback must use `extern "C"` calling conventions and adhere to
     /// the signature expected by the particular VkFFT build. This helper
     /// returns an error on bindings that do not expose callback pointers.
+    ///
+    /// # Safety
+    /// `callback` will be invoked from VkFFT-generated code and must remain
+    /// valid for the lifetime of the plan.
     pub unsafe fn set_callback_pointer(
         &mut self,
         _callback: unsafe extern "C" fn(*mut c_void),
@@ -628,7 +830,28 @@ impl VkFft {
 
     /// Finalize and create the VkFFT application.
     pub fn initialize(&mut self) -> Result<(), VkfftError> {
-        let res = unsafe { ffi::vkfft_initialize(&mut self.app as *mut _, self.config) };
+        #[cfg(feature = "tracing")]
+        let span = trace::initialize_span(&self.config).entered();
+
+        trace::validation(|| {
+            self.validate_buffer_sizes()?;
+            self.validate_omitted_axes()?;
+            self.validate_half_precision_layout()?;
+            self.validate_precision()
+        })
+        .map_err(VkfftError::Unsupported)?;
+
+        // Plans loaded from application strings compile nothing.
+        if self.config.loadApplicationFromString == 0 && unsafe { ffi::vkfft_glslang_load() } == 0 {
+            return Err(VkfftError::CompilerUnavailable);
+        }
+
+        let res = trace::kernel_phases(|| unsafe {
+            ffi::vkfft_initialize(&mut self.app as *mut _, self.config)
+        });
+        #[cfg(feature = "tracing")]
+        span.record("result", tracing::field::debug(res));
+
         if vkfft_ok(res) {
             self.initialized = true;
             Ok(())
@@ -641,10 +864,31 @@ impl VkFft {
     /// Record FFT dispatch into an existing command buffer.
     ///
     /// Direction is usually `-1` inverse, `1` forward in VkFFT.
+    ///
+    /// With profiling enabled (see [`VkFft::enable_profiling`]) the dispatches
+    /// are bracketed by timestamp queries.
+    ///
+    /// Fails with `VkfftError::InvalidArgument` if the [`Normalization`]
+    /// needs a scaling pass in `direction`; record those transforms with
+    /// [`VkFft::append_normalized`].
     pub fn append(
         &mut self,
         command_buffer: vk::CommandBuffer,
         direction: i32,
+    ) -> Result<(), VkfftError> {
+        if self.scale_factor(direction).is_some() {
+            return Err(VkfftError::InvalidArgument(
+                "append: Ortho and Forward normalization need append_normalized",
+            ));
+        }
+        Ok(self.append_unscaled(command_buffer, direction)?)
+    }
+
+    /// [`VkFft::append`] without the normalization check.
+    pub(crate) fn append_unscaled(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        direction: i32,
     ) -> Result<(), VkFftError> {
         if !self.initialized {
             return Err(VkFftError::from_result(
@@ -652,6 +896,10 @@ impl VkFft {
             ));
         }
 
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!("vkfft.append", direction, result = tracing::field::Empty)
+            .entered();
+
         let mut launch = unsafe { core::mem::zeroed::<ffi::VkFFTLaunchParams>() };
 
         // Many VkFFT versions want the command buffer passed as a raw handle inside launch params.
@@ -659,29 +907,43 @@ impl VkFft {
         let mut cmd = command_buffer;
         launch.commandBuffer = (&mut cmd as *mut vk::CommandBuffer).cast();
 
+        let slot = self
+            .profiler
+            .as_mut()
+            .map(|profiler| unsafe { profiler.begin(command_buffer, direction) });
+
         let res =
             unsafe { ffi::vkfft_append(&mut self.app as *mut _, direction, &mut launch as *mut _) };
 
+        if let (Some(profiler), Some(slot)) = (self.profiler.as_mut(), slot) {
+            unsafe { profiler.end(command_buffer, slot) };
+        }
+        #[cfg(feature = "tracing")]
+        span.record("result", tracing::field::debug(res));
         vkfft_check(res)
     }
 }
 
+impl Default for VkFft {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drop for VkFft {
     fn drop(&mut self) {
         if self.initialized {
             unsafe {
-                let _ = ffi::vkfft_delete(&mut self.app as *mut _);
+                ffi::vkfft_delete(&mut self.app as *mut _);
             }
         }
     }
 }
 
-
 #[cfg(all(feature = "wrapper", test))]
 mod tests {
-  
-    use ash::vk::Handle;
     use super::*;
+    use ash::vk::Handle;
     use core::ffi::c_void;
 
     unsafe extern "C" fn dummy_callback(_userdata: *mut c_void) {}
@@ -695,7 +957,7 @@ mod tests {
         // Current bindings do not expose callback pointer slots, but the API
         // shape should exist for forwards compatibility.
         assert!(res.is_err());
-   }
+    }
 
     #[test]
     fn configures_dimensions_and_buffers() {
@@ -710,7 +972,7 @@ mod tests {
         assert_eq!(fft.config.size[3], 1);
 
         let dummy_buffer = vk::Buffer::from_raw(0xDEADBEEF_u64);
-        fft.configure_buffers(dummy_buffer);
+        fft.configure_buffers(&[dummy_buffer], None).unwrap();
 
         let stored_ptr = fft.config.buffer as *mut vk::Buffer;
         assert_eq!(unsafe { *stored_ptr }, dummy_buffer);
@@ -722,18 +984,150 @@ mod tests {
 
         fft.configure_buffer_size(1024);
         assert_eq!(unsafe { *fft.config.bufferSize }, 1024);
-        assert_eq!(fft.config.bufferNum, 1);
+        assert!(fft.validate_buffer_sizes().is_ok());
+
+        // A single size cannot describe split buffers, and no longer hides
+        // them by resetting `bufferNum`.
+        let buffers = [1, 2].map(vk::Buffer::from_raw);
+        fft.configure_buffers(&buffers, None).unwrap();
+        fft.configure_buffer_size(1024);
+        assert_eq!(fft.config.bufferNum, 2);
+        let err = fft.validate_buffer_sizes().unwrap_err();
+        assert_eq!(err.code, ffi::VkFFTResult::VKFFT_ERROR_EMPTY_bufferSize);
+    }
+
+    #[test]
+    fn half_memory_needs_formatted_buffers() {
+        let mut fft = VkFft::new();
+        fft.configure_dimensions(&[1024]);
+        fft.config.halfPrecision = 1;
+        fft.config.halfPrecisionMemoryOnly = 1;
+        let [input, buffer, output] = [1, 2, 3].map(vk::Buffer::from_raw);
+        fft.configure_buffers(&[buffer], None).unwrap();
+        let err = fft.validate_half_precision_layout().unwrap_err();
+        assert_eq!(err.code, ffi::VkFFTResult::VKFFT_ERROR_EMPTY_inputBuffer);
+        fft.configure_buffers(&[input], Some(&[output])).unwrap();
+        assert!(fft.validate_half_precision_layout().is_err());
+
+        assert!(fft
+            .configure_formatted_buffers(input, 4096, buffer, 0, output, 4096)
+            .is_err());
+        fft.configure_formatted_buffers(input, 4096, buffer, 8192, output, 4096)
+            .unwrap();
+        assert!(fft.validate_half_precision_layout().is_ok());
+        assert_eq!(
+            (fft.config.isInputFormatted, fft.config.isOutputFormatted),
+            (1, 1)
+        );
+        assert_eq!(unsafe { *fft.config.buffer.cast::<vk::Buffer>() }, buffer);
+        assert_eq!(
+            unsafe { *fft.config.inputBuffer.cast::<vk::Buffer>() },
+            input
+        );
+        assert_eq!(
+            unsafe { *fft.config.outputBuffer.cast::<vk::Buffer>() },
+            output
+        );
+        assert_eq!(unsafe { *fft.config.bufferSize }, 8192);
+        assert_eq!(unsafe { *fft.config.inputBufferSize }, 4096);
+
+        // Going back to one in-place buffer drops the formatted sizes.
+        fft.configure_buffers(&[buffer], None).unwrap();
+        assert!(fft.config.inputBufferSize.is_null());
+    }
+
+    #[test]
+    fn configures_split_buffers() {
+        let mut fft = VkFft::new();
+        // The buffers hold one array, so their count is unrelated to batching.
+        fft.set_batch_count(4);
+        let buffers = [1, 2, 3].map(vk::Buffer::from_raw);
+        let split = |fft: &mut VkFft, sizes: &[u64]| fft.configure_split_buffer(&buffers, sizes);
+        split(&mut fft, &[4096, 4096, 1024]).unwrap();
+        assert_eq!(fft.config.bufferNum, 3);
+        assert_eq!(fft.buffer_sizes, [4096, 4096, 1024]);
+        assert_eq!(unsafe { *fft.config.bufferSize.add(2) }, 1024);
+        assert!(fft.validate_buffer_sizes().is_ok());
+
+        assert!(split(&mut fft, &[4096, 4096]).is_err());
+        assert!(fft.configure_split_buffer(&[], &[]).is_err());
+        assert!(split(&mut fft, &[4096, 0, 1024]).is_err());
+        assert!(split(&mut fft, &[4096, 2048, 1024]).is_err());
+        assert!(split(&mut fft, &[4096, 4096, 8192]).is_err());
+        // Blocks must hold whole complex elements: 8 bytes in single precision,
+        // 16 in double.
+        assert!(split(&mut fft, &[4100, 4100, 8]).is_err());
+        fft.set_double_precision(true);
+        assert!(split(&mut fft, &[4104, 4104, 8]).is_err());
+        assert!(split(&mut fft, &[4096, 4096, 8]).is_ok());
+        fft.set_double_precision(false);
+        // fp16 storage computed in fp32 transforms in fp32 buffers.
+        fft.config.halfPrecision = 1;
+        fft.config.halfPrecisionMemoryOnly = 1;
+        assert!(split(&mut fft, &[4100, 4100, 8]).is_err());
+        fft.config.halfPrecisionMemoryOnly = 0;
+        assert!(split(&mut fft, &[4100, 4100, 8]).is_ok());
+        fft.config.halfPrecision = 0;
+
+        fft.set_device_limits(DeviceLimits {
+            max_storage_buffer_range: 2048,
+            ..Default::default()
+        });
+        assert!(fft.validate_buffer_sizes().is_err());
+        assert!(split(&mut fft, &[2048, 2048, 16]).is_ok());
     }
 
     #[test]
     fn append_rejects_when_not_initialized() {
         let mut fft = VkFft::new();
-        assert_eq!(fft.initialized, false);
+        assert!(!fft.initialized);
 
         let err = fft
             .append(vk::CommandBuffer::null(), 1)
             .expect_err("append should fail before initialize");
-        assert_eq!(err, -1);
+        assert_eq!(
+            err,
+            VkfftError::Vkfft(ffi::VkFFTResult::VKFFT_ERROR_PLAN_NOT_INITIALIZED)
+        );
+
+        // A scaling normalization is rejected before the plan is looked at.
+        fft.set_normalization(Normalization::Forward);
+        assert_eq!(
+            fft.append(vk::CommandBuffer::null(), -1),
+            Err(VkfftError::InvalidArgument(
+                "append: Ortho and Forward normalization need append_normalized"
+            ))
+        );
+    }
+
+    #[test]
+    fn omitted_axes_are_validated() {
+        let mut fft = VkFft::new();
+        fft.configure_dimensions(&[64, 64, 16]);
+        fft.config.omitDimension = [1, 1, 1, 1];
+        fft.set_transformed_axes(&[0, 1]).unwrap();
+        assert_eq!(fft.config.omitDimension, [0, 0, 1, 0]);
+        assert!(fft.validate_omitted_axes().is_ok());
+        assert!(fft.set_transformed_axes(&[3]).is_err());
+
+        // Axis 3 is outside the 3D plan.
+        fft.set_axis_transform(3, false).unwrap();
+        let err = fft
+            .validate_omitted_axes()
+            .expect_err("axis 3 is beyond FFTdim");
+        assert_eq!(err.code, ffi::VkFFTResult::VKFFT_ERROR_UNSUPPORTED_FFT_OMIT);
+        assert!(err.message.contains("axis 3"));
+
+        fft.set_axis_transform(3, true).unwrap();
+        assert!(fft.validate_omitted_axes().is_ok());
+
+        fft.config.performR2C = 1;
+        fft.set_axis_transform(0, false).unwrap();
+        let err = fft
+            .validate_omitted_axes()
+            .expect_err("R2C axis 0 cannot be omitted");
+        assert!(err.message.contains("axis 0"));
 
+        assert!(fft.set_axis_transform(4, false).is_err());
     }
 }
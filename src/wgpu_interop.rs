@@ -0,0 +1,375 @@
+//! Run VkFFT plans on buffers owned by a `wgpu` device.
+//!
+//! `wgpu` exposes its Vulkan objects through `wgpu-hal`. This module pulls the
+//! raw `vk::Device`, `vk::Queue` and physical device out of a `wgpu::Device`,
+//! builds a [`VkFft`] plan on them and records the FFT straight into a
+//! `wgpu::CommandEncoder`, so no copy into a separate Vulkan context is needed.
+//!
+//! `wgpu-hal` does not hand out the raw `vk::Buffer` of buffers that `wgpu`
+//! allocated itself, so FFT buffers are created here with `ash` on the same
+//! device and then imported into `wgpu` with `create_buffer_from_hal`. The
+//! resulting [`WgpuFftBuffer`] can be bound in regular `wgpu` pipelines.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use ash::vk;
+use wgpu::hal::api::Vulkan;
+
+use crate::context::find_memory_type_index;
+use crate::vkfft::{VkFft, VkfftError};
+
+/// Raw Vulkan handles backing a `wgpu::Device`.
+#[derive(Clone)]
+pub struct WgpuVulkanHandles {
+    pub instance: ash::Instance,
+    pub device: ash::Device,
+    pub physical_device: vk::PhysicalDevice,
+    pub queue: vk::Queue,
+    pub queue_family_index: u32,
+}
+
+/// Extract the Vulkan handles behind `device`.
+///
+/// Returns `None` when `device` does not run on the Vulkan backend.
+///
+/// # Safety
+/// The returned handles are owned by `wgpu`. They must not be destroyed and
+/// must not outlive `device`. The queue is shared with `wgpu`, so submissions
+/// made through it must not race with `wgpu::Queue::submit`.
+pub unsafe fn vulkan_handles(device: &wgpu::Device) -> Option<WgpuVulkanHandles> {
+    device.as_hal::<Vulkan, _, _>(|hal_device| {
+        hal_device.map(|hal_device| WgpuVulkanHandles {
+            instance: hal_device.shared_instance().raw_instance().clone(),
+            device: hal_device.raw_device().clone(),
+            physical_device: hal_device.raw_physical_device(),
+            queue: hal_device.raw_queue(),
+            queue_family_index: hal_device.queue_family_index(),
+        })
+    })
+}
+
+/// Storage buffer allocated on a `wgpu` device that VkFFT can address.
+///
+/// The Vulkan buffer is owned by the wrapped `wgpu::Buffer`; the device memory
+/// bound to it is owned by this struct and released on drop. Dropping the
+/// buffer waits for the `wgpu` device to finish its submitted work, since the
+/// memory may only be freed once `wgpu` has destroyed the buffer.
+pub struct WgpuFftBuffer {
+    buffer: wgpu::Buffer,
+    raw: vk::Buffer,
+    size: vk::DeviceSize,
+    memory: vk::DeviceMemory,
+    device: ash::Device,
+    wgpu_device: wgpu::Device,
+}
+
+impl WgpuFftBuffer {
+    /// The `wgpu` view of the buffer, for use in bind groups and copies.
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /// The raw Vulkan buffer handle.
+    pub fn raw(&self) -> vk::Buffer {
+        self.raw
+    }
+
+    /// Size of the buffer in bytes.
+    pub fn size(&self) -> vk::DeviceSize {
+        self.size
+    }
+}
+
+impl Drop for WgpuFftBuffer {
+    fn drop(&mut self) {
+        // `wgpu` destroys the vk::Buffer once no submission uses it any more,
+        // which polling with `Wait` guarantees; the memory is ours.
+        self.buffer.destroy();
+        let _ = self.wgpu_device.poll(wgpu::Maintain::Wait);
+        unsafe {
+            self.device.free_memory(self.memory, None);
+        }
+    }
+}
+
+/// Command pool and fence VkFFT uses while building the plan.
+///
+/// Kept in its own struct so it is dropped after the plan that refers to it.
+struct InitResources {
+    device: ash::Device,
+    command_pool: vk::CommandPool,
+    fence: vk::Fence,
+}
+
+impl Drop for InitResources {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_fence(self.fence, None);
+            self.device.destroy_command_pool(self.command_pool, None);
+        }
+    }
+}
+
+/// VkFFT plan bound to a `wgpu` device.
+///
+/// Configure the plan through [`WgpuFft::plan_mut`], attach buffers created by
+/// [`WgpuFft::create_buffer`], call [`WgpuFft::initialize`] and then record the
+/// transform with [`WgpuFft::record`].
+pub struct WgpuFft {
+    // Boxed because VkFFTConfiguration stores pointers into the plan.
+    fft: Box<VkFft>,
+    handles: WgpuVulkanHandles,
+    _resources: InitResources,
+}
+
+impl WgpuFft {
+    /// Create a plan on the Vulkan device behind `device`.
+    ///
+    /// # Errors
+    /// Returns [`VkfftError::UnsupportedBackend`] if `device` is not a Vulkan
+    /// device, or a Vulkan error if the command pool or fence cannot be created.
+    ///
+    /// # Safety
+    /// `initialize` submits work on `wgpu`'s queue; the caller must ensure no
+    /// `wgpu` submission happens concurrently. The plan must be dropped before
+    /// `device`.
+    pub unsafe fn new(device: &wgpu::Device) -> Result<Self, VkfftError> {
+        let handles = vulkan_handles(device).ok_or(VkfftError::UnsupportedBackend)?;
+
+        let command_pool_info = vk::CommandPoolCreateInfo {
+            s_type: vk::StructureType::COMMAND_POOL_CREATE_INFO,
+            queue_family_index: handles.queue_family_index,
+            flags: vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER,
+            ..Default::default()
+        };
+        let command_pool = handles
+            .device
+            .create_command_pool(&command_pool_info, None)?;
+
+        let fence_info = vk::FenceCreateInfo {
+            s_type: vk::StructureType::FENCE_CREATE_INFO,
+            ..Default::default()
+        };
+        let fence = match handles.device.create_fence(&fence_info, None) {
+            Ok(fence) => fence,
+            Err(err) => {
+                handles.device.destroy_command_pool(command_pool, None);
+                return Err(err.into());
+            }
+        };
+
+        let resources = InitResources {
+            device: handles.device.clone(),
+            command_pool,
+            fence,
+        };
+
+        let mut fft = Box::new(VkFft::new());
+        fft.configure_vulkan(
+            handles.physical_device,
+            handles.device.handle(),
+            handles.queue,
+            command_pool,
+        );
+        fft.configure_fence(fence);
+
+        Ok(Self {
+            fft,
+            handles,
+            _resources: resources,
+        })
+    }
+
+    /// The raw Vulkan handles of the `wgpu` device this plan runs on.
+    pub fn handles(&self) -> &WgpuVulkanHandles {
+        &self.handles
+    }
+
+    /// Access the underlying plan to set dimensions, precision, batching, etc.
+    pub fn plan_mut(&mut self) -> &mut VkFft {
+        &mut self.fft
+    }
+
+    /// Allocate a device-local storage buffer usable by both VkFFT and `wgpu`.
+    ///
+    /// `usage` is added to `STORAGE | COPY_SRC | COPY_DST`. Mappable usages are
+    /// rejected because the memory is not host visible; stage through a
+    /// separate `wgpu` buffer instead.
+    ///
+    /// # Errors
+    /// Returns a Vulkan error if allocation fails, or
+    /// `VK_ERROR_FEATURE_NOT_PRESENT` if `usage` asks for mapping.
+    ///
+    /// # Safety
+    /// `device` must be the `wgpu::Device` this plan was created from.
+    pub unsafe fn create_buffer(
+        &self,
+        device: &wgpu::Device,
+        size: vk::DeviceSize,
+        usage: wgpu::BufferUsages,
+    ) -> Result<WgpuFftBuffer, VkfftError> {
+        if usage.intersects(wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::MAP_WRITE) {
+            return Err(vk::Result::ERROR_FEATURE_NOT_PRESENT.into());
+        }
+
+        let raw_device = &self.handles.device;
+        let buffer_info = vk::BufferCreateInfo {
+            s_type: vk::StructureType::BUFFER_CREATE_INFO,
+            size,
+            usage: vk_buffer_usage(usage),
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        };
+        let raw = raw_device.create_buffer(&buffer_info, None)?;
+
+        let requirements = raw_device.get_buffer_memory_requirements(raw);
+        let properties = self
+            .handles
+            .instance
+            .get_physical_device_memory_properties(self.handles.physical_device);
+        let Some(memory_type_index) = find_memory_type_index(
+            &properties,
+            requirements.memory_type_bits,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        ) else {
+            raw_device.destroy_buffer(raw, None);
+            return Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY.into());
+        };
+
+        let allocation_info = vk::MemoryAllocateInfo {
+            s_type: vk::StructureType::MEMORY_ALLOCATE_INFO,
+            allocation_size: requirements.size,
+            memory_type_index,
+            ..Default::default()
+        };
+        let memory = match raw_device.allocate_memory(&allocation_info, None) {
+            Ok(memory) => memory,
+            Err(err) => {
+                raw_device.destroy_buffer(raw, None);
+                return Err(err.into());
+            }
+        };
+        if let Err(err) = raw_device.bind_buffer_memory(raw, memory, 0) {
+            raw_device.destroy_buffer(raw, None);
+            raw_device.free_memory(memory, None);
+            return Err(err.into());
+        }
+
+        let hal_buffer = wgpu::hal::vulkan::Device::buffer_from_raw(raw);
+        let buffer = device.create_buffer_from_hal::<Vulkan>(
+            hal_buffer,
+            &wgpu::BufferDescriptor {
+                label: Some("vkfft buffer"),
+                size,
+                usage: usage
+                    | wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+
+        Ok(WgpuFftBuffer {
+            buffer,
+            raw,
+            size,
+            memory,
+            device: raw_device.clone(),
+            wgpu_device: device.clone(),
+        })
+    }
+
+    /// Attach input (and optional output) buffers and their sizes to the plan.
+    ///
+    /// See [`VkFft::configure_buffers`] for the rules on counts.
+    pub fn configure_buffers(
+        &mut self,
+        input_buffers: &[&WgpuFftBuffer],
+        output_buffers: Option<&[&WgpuFftBuffer]>,
+    ) -> Result<(), &'static str> {
+        let inputs: Vec<vk::Buffer> = input_buffers.iter().map(|b| b.raw).collect();
+        let outputs: Option<Vec<vk::Buffer>> =
+            output_buffers.map(|out| out.iter().map(|b| b.raw).collect());
+        self.fft.configure_buffers(&inputs, outputs.as_deref())?;
+
+        let sizes: Vec<u64> = input_buffers.iter().map(|b| b.size).collect();
+        self.fft.set_buffer_sizes(&sizes);
+        Ok(())
+    }
+
+    /// Build the VkFFT plan.
+    ///
+    /// This compiles shaders and may submit upload work on `wgpu`'s queue.
+    pub fn initialize(&mut self) -> Result<(), VkfftError> {
+        self.fft.initialize()
+    }
+
+    /// Record the transform into a `wgpu` command encoder.
+    ///
+    /// `wgpu` does not track the accesses VkFFT makes, so a full
+    /// compute/transfer memory barrier is recorded before and after the
+    /// dispatches. Direction follows [`VkFft::append`].
+    ///
+    /// # Safety
+    /// `encoder` must come from the device this plan was created on, and every
+    /// buffer attached to the plan must still be alive when the command buffer
+    /// executes.
+    pub unsafe fn record(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        direction: i32,
+    ) -> Result<(), VkfftError> {
+        let fft = &mut self.fft;
+        let device = &self.handles.device;
+        encoder.as_hal_mut::<Vulkan, _, _>(|hal_encoder| {
+            let hal_encoder = hal_encoder.ok_or(VkfftError::UnsupportedBackend)?;
+            let command_buffer = hal_encoder.raw_handle();
+
+            record_barrier(device, command_buffer);
+            fft.append(command_buffer, direction)?;
+            record_barrier(device, command_buffer);
+            Ok(())
+        })
+    }
+}
+
+fn vk_buffer_usage(usage: wgpu::BufferUsages) -> vk::BufferUsageFlags {
+    let mut flags = vk::BufferUsageFlags::STORAGE_BUFFER
+        | vk::BufferUsageFlags::TRANSFER_SRC
+        | vk::BufferUsageFlags::TRANSFER_DST;
+    if usage.contains(wgpu::BufferUsages::UNIFORM) {
+        flags |= vk::BufferUsageFlags::UNIFORM_BUFFER;
+    }
+    if usage.contains(wgpu::BufferUsages::VERTEX) {
+        flags |= vk::BufferUsageFlags::VERTEX_BUFFER;
+    }
+    if usage.contains(wgpu::BufferUsages::INDEX) {
+        flags |= vk::BufferUsageFlags::INDEX_BUFFER;
+    }
+    if usage.contains(wgpu::BufferUsages::INDIRECT) {
+        flags |= vk::BufferUsageFlags::INDIRECT_BUFFER;
+    }
+    flags
+}
+
+unsafe fn record_barrier(device: &ash::Device, command_buffer: vk::CommandBuffer) {
+    let barrier = vk::MemoryBarrier {
+        s_type: vk::StructureType::MEMORY_BARRIER,
+        src_access_mask: vk::AccessFlags::SHADER_WRITE | vk::AccessFlags::TRANSFER_WRITE,
+        dst_access_mask: vk::AccessFlags::SHADER_READ
+            | vk::AccessFlags::SHADER_WRITE
+            | vk::AccessFlags::TRANSFER_READ
+            | vk::AccessFlags::TRANSFER_WRITE,
+        ..Default::default()
+    };
+    let stages = vk::PipelineStageFlags::COMPUTE_SHADER | vk::PipelineStageFlags::TRANSFER;
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        stages,
+        stages | vk::PipelineStageFlags::VERTEX_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER,
+        vk::DependencyFlags::empty(),
+        &[barrier],
+        &[],
+        &[],
+    );
+}
@@ -0,0 +1,346 @@
+//! Reference-accuracy suite: runs VkFFT plans on a real Vulkan device and
+//! compares the output with `rustfft`/`realfft` computed in f64.
+//!
+//! Intended for CI on Mesa's lavapipe; set `VKFFT_TEST_DEVICE` to a device
+//! name substring to run on other hardware. Per-case errors are printed, run
+//! with `--nocapture` to see them.
+
+#[macro_use]
+mod common;
+
+use std::f64::consts::PI;
+
+use num_complex::Complex;
+use realfft::RealFftPlanner;
+use rustfft::FftPlanner;
+use vkfft_bindings::Precision;
+
+use common::{
+    check, check_computed, from_complex, round_to, run_half_memory_only, run_in_place, signal,
+    to_complex, TestDevice,
+};
+
+const FORWARD: i32 = -1;
+const INVERSE: i32 = 1;
+
+const PRECISIONS: [Precision; 3] = [Precision::Single, Precision::Double, Precision::Half];
+
+/// Apply a 1D transform along `axis` of `data` (axis 0 contiguous).
+fn transform_axis(
+    data: &mut [Complex<f64>],
+    sizes: &[usize],
+    axis: usize,
+    inverse: bool,
+    planner: &mut FftPlanner<f64>,
+) {
+    let n = sizes[axis];
+    let stride: usize = sizes[..axis].iter().product();
+    let fft = if inverse {
+        planner.plan_fft_inverse(n)
+    } else {
+        planner.plan_fft_forward(n)
+    };
+    let mut line = vec![Complex::default(); n];
+    let starts = (0..data.len() / (stride * n))
+        .flat_map(|outer| (0..stride).map(move |inner| outer * stride * n + inner));
+    for start in starts {
+        for (i, value) in line.iter_mut().enumerate() {
+            *value = data[start + i * stride];
+        }
+        fft.process(&mut line);
+        for (i, value) in line.iter().enumerate() {
+            data[start + i * stride] = *value;
+        }
+    }
+}
+
+/// Unnormalized multidimensional C2C reference over `batches` contiguous
+/// systems of `sizes`.
+fn reference_c2c(
+    input: &[Complex<f64>],
+    sizes: &[usize],
+    batches: usize,
+    inverse: bool,
+) -> Vec<Complex<f64>> {
+    let mut planner = FftPlanner::new();
+    let mut output = input.to_vec();
+    let total: usize = sizes.iter().product();
+    for system in output.chunks_exact_mut(total).take(batches) {
+        for axis in 0..sizes.len() {
+            transform_axis(system, sizes, axis, inverse, &mut planner);
+        }
+    }
+    output
+}
+
+/// Run a contiguous in-place C2C transform at every supported precision, and
+/// out of place with fp16 storage computed in fp32.
+fn check_c2c(device: &TestDevice, sizes: &[usize], batches: usize, direction: i32) {
+    let total: usize = sizes.iter().product::<usize>() * batches;
+    let sizes_u64: Vec<u64> = sizes.iter().map(|&n| n as u64).collect();
+    let label = format!(
+        "c2c {:?} x{batches} {}",
+        sizes,
+        if direction == FORWARD { "fwd" } else { "inv" }
+    );
+
+    for storage in PRECISIONS {
+        if !device.supports(storage) {
+            println!(
+                "{label:<40} {storage:?}: skipped, unsupported on {}",
+                device.name
+            );
+            continue;
+        }
+        let input = round_to(storage, &signal(2 * total, total as u64));
+        let result = run_in_place(
+            device,
+            |plan| {
+                plan.configure_dimensions(&sizes_u64);
+                plan.set_batch_count(batches as u64);
+            },
+            storage,
+            &input,
+            2 * total,
+            direction,
+            2 * total,
+        );
+        let reference = reference_c2c(&to_complex(&input), sizes, batches, direction == INVERSE);
+        check(&label, storage, &result, &from_complex(&reference));
+    }
+
+    if !device.supports_half_memory_only() {
+        println!(
+            "{label:<40} Half/Single: skipped, unsupported on {}",
+            device.name
+        );
+        return;
+    }
+    let input = round_to(Precision::Half, &signal(2 * total, total as u64));
+    let result = run_half_memory_only(
+        device,
+        |plan| {
+            plan.configure_dimensions(&sizes_u64);
+            plan.set_batch_count(batches as u64);
+        },
+        &input,
+        2 * total,
+        direction,
+        2 * total,
+    );
+    let reference = reference_c2c(&to_complex(&input), sizes, batches, direction == INVERSE);
+    check_computed(
+        &label,
+        Precision::Half,
+        Precision::Single,
+        &result,
+        &from_complex(&reference),
+    );
+}
+
+#[test]
+fn c2c_power_of_two() {
+    let device = test_device!();
+    for n in [2, 8, 64, 256, 1024, 4096, 65536] {
+        check_c2c(&device, &[n], 1, FORWARD);
+    }
+}
+
+#[test]
+fn c2c_mixed_radix() {
+    let device = test_device!();
+    // 2^3 3^2 5, 2 3 5 7 11, 2^2 5^3, 3^4 7, 13^2.
+    for n in [360, 2310, 500, 567, 169] {
+        check_c2c(&device, &[n], 1, FORWARD);
+    }
+}
+
+#[test]
+fn c2c_primes() {
+    let device = test_device!();
+    // Small primes go through Rader's algorithm, large ones through Bluestein.
+    for n in [17, 31, 127, 1009, 4093, 8191] {
+        check_c2c(&device, &[n], 1, FORWARD);
+    }
+}
+
+#[test]
+fn c2c_inverse() {
+    let device = test_device!();
+    for n in [64, 360, 127, 4093] {
+        check_c2c(&device, &[n], 1, INVERSE);
+    }
+}
+
+#[test]
+fn c2c_multidimensional() {
+    let device = test_device!();
+    check_c2c(&device, &[64, 32], 1, FORWARD);
+    check_c2c(&device, &[100, 30], 1, FORWARD);
+    check_c2c(&device, &[16, 12, 10], 1, FORWARD);
+    check_c2c(&device, &[32, 32, 32], 1, INVERSE);
+    check_c2c(&device, &[8, 6, 5, 4], 1, FORWARD);
+}
+
+#[test]
+fn c2c_batched() {
+    let device = test_device!();
+    check_c2c(&device, &[120], 7, FORWARD);
+    check_c2c(&device, &[17], 33, INVERSE);
+    check_c2c(&device, &[32, 24], 5, FORWARD);
+}
+
+#[test]
+fn c2c_padded_rows() {
+    let device = test_device!();
+    // A 100x30 transform stored in rows of 128 complex elements.
+    let (width, height, pitch) = (100usize, 30usize, 128usize);
+    let label = format!("c2c [{width}, {height}] pitch {pitch}");
+
+    for storage in PRECISIONS {
+        if !device.supports(storage) {
+            continue;
+        }
+        let dense = round_to(storage, &signal(2 * width * height, 7));
+        let mut padded = vec![0.0; 2 * pitch * height];
+        for row in 0..height {
+            padded[2 * row * pitch..][..2 * width]
+                .copy_from_slice(&dense[2 * row * width..][..2 * width]);
+        }
+
+        let result = run_in_place(
+            &device,
+            |plan| {
+                plan.configure_dimensions(&[width as u64, height as u64]);
+                let span = (pitch * height) as u64;
+                plan.set_strides([pitch as u64, span, span, span], None, None);
+            },
+            storage,
+            &padded,
+            2 * pitch * height,
+            FORWARD,
+            2 * pitch * height,
+        );
+        let result: Vec<f64> = (0..height)
+            .flat_map(|row| result[2 * row * pitch..][..2 * width].to_vec())
+            .collect();
+        let reference = reference_c2c(&to_complex(&dense), &[width, height], 1, false);
+        check(&label, storage, &result, &from_complex(&reference));
+    }
+}
+
+/// In-place R2C: each row of `sizes[0]` reals is padded to `2 * (n / 2 + 1)`
+/// scalars and holds `n / 2 + 1` complex bins after the transform.
+fn check_r2c(device: &TestDevice, sizes: &[usize]) {
+    let n = sizes[0];
+    let bins = n / 2 + 1;
+    let rows: usize = sizes[1..].iter().product();
+    let sizes_u64: Vec<u64> = sizes.iter().map(|&n| n as u64).collect();
+    let label = format!("r2c {sizes:?}");
+
+    let mut real_planner = RealFftPlanner::<f64>::new();
+    let r2c = real_planner.plan_fft_forward(n);
+
+    for storage in PRECISIONS {
+        if !device.supports(storage) {
+            continue;
+        }
+        let real = round_to(storage, &signal(n * rows, (n * rows) as u64));
+        let mut padded = vec![0.0; 2 * bins * rows];
+        for row in 0..rows {
+            padded[2 * bins * row..][..n].copy_from_slice(&real[n * row..][..n]);
+        }
+
+        let result = run_in_place(
+            device,
+            |plan| {
+                plan.configure_dimensions(&sizes_u64);
+                plan.set_r2c(true);
+            },
+            storage,
+            &padded,
+            2 * bins * rows,
+            FORWARD,
+            2 * bins * rows,
+        );
+
+        // Real transform along axis 0, complex transforms along the rest.
+        let mut reference = vec![Complex::default(); bins * rows];
+        for row in 0..rows {
+            let mut line = real[n * row..][..n].to_vec();
+            r2c.process(&mut line, &mut reference[bins * row..][..bins])
+                .unwrap();
+        }
+        let mut reference_sizes = sizes.to_vec();
+        reference_sizes[0] = bins;
+        let mut planner = FftPlanner::new();
+        for axis in 1..sizes.len() {
+            transform_axis(&mut reference, &reference_sizes, axis, false, &mut planner);
+        }
+        check(&label, storage, &result, &from_complex(&reference));
+    }
+}
+
+#[test]
+fn r2c_in_place() {
+    let device = test_device!();
+    check_r2c(&device, &[256]);
+    check_r2c(&device, &[1000]);
+    check_r2c(&device, &[64, 48]);
+    check_r2c(&device, &[100, 12]);
+    check_r2c(&device, &[30, 10, 6]);
+}
+
+/// Naive DCT in FFTW's convention (`REDFT10` for type 2, `REDFT11` for 4).
+fn reference_dct(input: &[f64], kind: u64) -> Vec<f64> {
+    let n = input.len() as f64;
+    let shift = if kind == 4 { 0.5 } else { 0.0 };
+    (0..input.len())
+        .map(|k| {
+            2.0 * input
+                .iter()
+                .enumerate()
+                .map(|(j, x)| x * (PI * (j as f64 + 0.5) * (k as f64 + shift) / n).cos())
+                .sum::<f64>()
+        })
+        .collect()
+}
+
+fn check_dct(device: &TestDevice, kind: u64, n: usize) {
+    let label = format!("dct-{} [{n}]", if kind == 2 { "II" } else { "IV" });
+    for storage in PRECISIONS {
+        if !device.supports(storage) {
+            continue;
+        }
+        let input = round_to(storage, &signal(n, n as u64 + kind));
+        let result = run_in_place(
+            device,
+            |plan| {
+                plan.configure_dimensions(&[n as u64]);
+                plan.set_dct(kind).unwrap();
+            },
+            storage,
+            &input,
+            n,
+            FORWARD,
+            n,
+        );
+        check(&label, storage, &result, &reference_dct(&input, kind));
+    }
+}
+
+#[test]
+fn dct_2() {
+    let device = test_device!();
+    for n in [16, 64, 100, 256, 17] {
+        check_dct(&device, 2, n);
+    }
+}
+
+#[test]
+fn dct_4() {
+    let device = test_device!();
+    for n in [16, 64, 100, 256, 34] {
+        check_dct(&device, 4, n);
+    }
+}
@@ -0,0 +1,301 @@
+//! Shared setup for tests that run real transforms.
+//!
+//! Tests pick a Vulkan device (Mesa's lavapipe by default, or the device whose
+//! name contains `VKFFT_TEST_DEVICE`) and skip themselves when no Vulkan
+//! loader or device is available. With `VKFFT_TEST_DEVICE` or `CI` set, a
+//! missing device fails the tests instead.
+
+#![allow(dead_code)]
+
+use half::f16;
+use num_complex::Complex;
+use vkfft_bindings::element::PrecisionFlags;
+use vkfft_bindings::{DeviceContext, DeviceSelector, FftContext, Precision, VkFft};
+
+pub struct TestDevice {
+    ctx: FftContext,
+    pub name: String,
+}
+
+impl TestDevice {
+    /// Open the test device, or `None` if Vulkan is unavailable.
+    ///
+    /// # Panics
+    /// If the device cannot be opened while `VKFFT_TEST_DEVICE` or `CI` is
+    /// set, so CI cannot pass without running the suite.
+    pub fn open() -> Option<Self> {
+        let requested = std::env::var("VKFFT_TEST_DEVICE").ok();
+        let required = requested.is_some() || std::env::var_os("CI").is_some();
+        let selector = requested.map_or(DeviceSelector::Cpu, DeviceSelector::Name);
+        match FftContext::new(selector) {
+            Ok(ctx) => Some(Self {
+                name: ctx.name().to_string(),
+                ctx,
+            }),
+            Err(err) if required => {
+                panic!("no Vulkan test device ({err}) although VKFFT_TEST_DEVICE or CI is set")
+            }
+            Err(_) => None,
+        }
+    }
+
+    pub fn ctx(&self) -> &DeviceContext {
+        &self.ctx
+    }
+
+    /// Whether kernels computing at `storage` precision can run on this
+    /// device.
+    pub fn supports(&self, storage: Precision) -> bool {
+        let limits = self.ctx.limits();
+        match storage {
+            Precision::Half => limits.storage16 && limits.float16,
+            Precision::Single => true,
+            Precision::Double => limits.float64,
+        }
+    }
+
+    /// Whether fp16 storage computed in fp32 can run on this device.
+    pub fn supports_half_memory_only(&self) -> bool {
+        self.ctx.limits().storage16
+    }
+}
+
+/// Open the test device or return early from the calling test.
+macro_rules! test_device {
+    () => {
+        match common::TestDevice::open() {
+            Some(device) => device,
+            None => {
+                eprintln!("skipping: no Vulkan device available");
+                return;
+            }
+        }
+    };
+}
+
+/// Bytes per scalar for a storage precision.
+pub fn scalar_size(storage: Precision) -> usize {
+    match storage {
+        Precision::Half => 2,
+        Precision::Single => 4,
+        Precision::Double => 8,
+    }
+}
+
+/// Round `values` to `storage` precision and return the rounded values.
+pub fn round_to(storage: Precision, values: &[f64]) -> Vec<f64> {
+    values
+        .iter()
+        .map(|&v| match storage {
+            Precision::Half => f16::from_f64(v).to_f64(),
+            Precision::Single => v as f32 as f64,
+            Precision::Double => v,
+        })
+        .collect()
+}
+
+pub fn encode(storage: Precision, values: &[f64], bytes: &mut [u8]) {
+    let size = scalar_size(storage);
+    for (v, out) in values.iter().zip(bytes.chunks_exact_mut(size)) {
+        match storage {
+            Precision::Half => out.copy_from_slice(&f16::from_f64(*v).to_le_bytes()),
+            Precision::Single => out.copy_from_slice(&(*v as f32).to_le_bytes()),
+            Precision::Double => out.copy_from_slice(&v.to_le_bytes()),
+        }
+    }
+}
+
+pub fn decode(storage: Precision, bytes: &[u8], count: usize) -> Vec<f64> {
+    bytes
+        .chunks_exact(scalar_size(storage))
+        .take(count)
+        .map(|b| match storage {
+            Precision::Half => f16::from_le_bytes([b[0], b[1]]).to_f64(),
+            Precision::Single => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            Precision::Double => f64::from_le_bytes(b.try_into().unwrap()),
+        })
+        .collect()
+}
+
+/// Deterministic pseudo-random values in [-1, 1).
+pub fn signal(len: usize, seed: u64) -> Vec<f64> {
+    let mut state = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 11) as f64 / (1u64 << 53) as f64) * 2.0 - 1.0
+        })
+        .collect()
+}
+
+pub fn to_complex(interleaved: &[f64]) -> Vec<Complex<f64>> {
+    interleaved
+        .chunks_exact(2)
+        .map(|c| Complex::new(c[0], c[1]))
+        .collect()
+}
+
+pub fn from_complex(values: &[Complex<f64>]) -> Vec<f64> {
+    values.iter().flat_map(|c| [c.re, c.im]).collect()
+}
+
+/// Error of a result against its reference.
+#[derive(Debug, Copy, Clone)]
+pub struct Errors {
+    /// max |a - b| / max |b|
+    pub max_rel: f64,
+    /// ||a - b||_2 / ||b||_2
+    pub l2_rel: f64,
+}
+
+pub fn errors(result: &[f64], reference: &[f64]) -> Errors {
+    assert_eq!(result.len(), reference.len());
+    let mut max_diff = 0.0_f64;
+    let mut max_ref = 0.0_f64;
+    let mut diff2 = 0.0_f64;
+    let mut ref2 = 0.0_f64;
+    for (a, b) in result.iter().zip(reference) {
+        let d = (a - b).abs();
+        max_diff = max_diff.max(d);
+        max_ref = max_ref.max(b.abs());
+        diff2 += d * d;
+        ref2 += b * b;
+    }
+    Errors {
+        max_rel: max_diff / max_ref.max(f64::MIN_POSITIVE),
+        l2_rel: (diff2 / ref2.max(f64::MIN_POSITIVE)).sqrt(),
+    }
+}
+
+/// Acceptance thresholds as (max relative, L2 relative) for a storage and
+/// compute precision.
+///
+/// fp16 storage computed in fp32 (`halfPrecisionMemoryOnly`) is dominated by
+/// rounding the input and output to fp16; fp16 computation also rounds every
+/// butterfly.
+pub fn thresholds(storage: Precision, compute: Precision) -> (f64, f64) {
+    match (storage, compute) {
+        (Precision::Half, Precision::Half) => (3e-2, 1e-2),
+        (Precision::Half, _) => (1e-2, 5e-3),
+        (Precision::Single, _) => (1e-4, 1e-5),
+        (Precision::Double, _) => (1e-11, 1e-12),
+    }
+}
+
+/// Check a result computed at its storage precision.
+pub fn check(label: &str, storage: Precision, result: &[f64], reference: &[f64]) {
+    check_computed(label, storage, storage, result, reference);
+}
+
+pub fn check_computed(
+    label: &str,
+    storage: Precision,
+    compute: Precision,
+    result: &[f64],
+    reference: &[f64],
+) {
+    let err = errors(result, reference);
+    let (max_tol, l2_tol) = thresholds(storage, compute);
+    println!(
+        "{label:<40} {storage:?}: max rel {:.3e} (tol {:.0e}), l2 rel {:.3e} (tol {:.0e})",
+        err.max_rel, max_tol, err.l2_rel, l2_tol
+    );
+    assert!(
+        err.max_rel <= max_tol && err.l2_rel <= l2_tol,
+        "{label} ({storage:?}) exceeds tolerance: {err:?}"
+    );
+}
+
+/// Upload `values` (in storage scalars), run `plan` in place at the storage
+/// precision in `direction` and return the first `out_count` scalars of the
+/// buffer.
+pub fn run_in_place(
+    device: &TestDevice,
+    configure: impl FnOnce(&mut VkFft),
+    storage: Precision,
+    values: &[f64],
+    buffer_scalars: usize,
+    direction: i32,
+    out_count: usize,
+) -> Vec<f64> {
+    let ctx = device.ctx();
+    let size = (buffer_scalars * scalar_size(storage)) as u64;
+    let buffer = ctx.create_host_buffer(size).expect("buffer allocation");
+    unsafe {
+        buffer
+            .with_mapped_bytes(|bytes| {
+                bytes.fill(0);
+                encode(storage, values, bytes);
+            })
+            .unwrap();
+    }
+
+    let mut plan = ctx.create_plan();
+    plan.set_precision_flags(PrecisionFlags::new(storage, storage).unwrap());
+    configure(&mut plan);
+    plan.configure_buffers(&[buffer.raw()], None).unwrap();
+    plan.set_buffer_sizes(&[size]);
+    plan.initialize().expect("plan initialization");
+    ctx.run(|command_buffer| plan.append(command_buffer, direction))
+        .expect("transform");
+
+    unsafe {
+        buffer
+            .with_mapped_bytes(|bytes| decode(storage, bytes, out_count))
+            .unwrap()
+    }
+}
+
+/// Run `plan` on fp16 `values` computed in fp32: VkFFT reads an fp16 input
+/// buffer, transforms in an fp32 buffer and writes an fp16 output buffer of
+/// the same layout, whose first `out_count` scalars are returned.
+pub fn run_half_memory_only(
+    device: &TestDevice,
+    configure: impl FnOnce(&mut VkFft),
+    values: &[f64],
+    buffer_scalars: usize,
+    direction: i32,
+    out_count: usize,
+) -> Vec<f64> {
+    let ctx = device.ctx();
+    let size = (buffer_scalars * scalar_size(Precision::Half)) as u64;
+    let input = ctx.create_host_buffer(size).expect("buffer allocation");
+    let output = ctx.create_host_buffer(size).expect("buffer allocation");
+    let buffer = ctx
+        .create_device_buffer((buffer_scalars * scalar_size(Precision::Single)) as u64)
+        .expect("buffer allocation");
+    unsafe {
+        input
+            .with_mapped_bytes(|bytes| {
+                bytes.fill(0);
+                encode(Precision::Half, values, bytes);
+            })
+            .unwrap();
+    }
+
+    let mut plan = ctx.create_plan();
+    plan.set_precision_flags(PrecisionFlags::new(Precision::Half, Precision::Single).unwrap());
+    configure(&mut plan);
+    plan.configure_formatted_buffers(
+        input.raw(),
+        input.size(),
+        buffer.raw(),
+        buffer.size(),
+        output.raw(),
+        output.size(),
+    )
+    .unwrap();
+    plan.initialize().expect("plan initialization");
+    ctx.run(|command_buffer| plan.append(command_buffer, direction))
+        .expect("transform");
+
+    unsafe {
+        output
+            .with_mapped_bytes(|bytes| decode(Precision::Half, bytes, out_count))
+            .unwrap()
+    }
+}
@@ -0,0 +1,23 @@
+#pragma once
+
+// Trace hook shared by the shim and the generated bindings. Kept free of
+// VkFFT includes so the shim can include it before interposing calls.
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Events reported to the trace hook during vkfft_initialize.
+#define VKFFT_TRACE_COMPILE_BEGIN 0
+#define VKFFT_TRACE_COMPILE_END 1
+#define VKFFT_TRACE_PIPELINE_BEGIN 2
+// `result` carries the VkResult of vkCreateComputePipelines.
+#define VKFFT_TRACE_PIPELINE_END 3
+
+typedef void (*vkfft_trace_hook)(void* user, int event, int result);
+
+// Install a hook for the calling thread; pass NULL to remove it.
+void vkfft_set_trace_hook(vkfft_trace_hook hook, void* user);
+
+#ifdef __cplusplus
+}
+#endif
@@ -3,6 +3,7 @@
 
 #include "vkFFT.h"
 #include "vkFFT/vkFFT_Structs/vkFFT_Structs.h"
+#include "vkfft_trace.h"
 
 // Declarations for our C ABI shim
 #ifdef __cplusplus
@@ -12,6 +13,9 @@ extern "C" {
 VkFFTResult vkfft_initialize(VkFFTApplication* app, VkFFTConfiguration config);
 VkFFTResult vkfft_append(VkFFTApplication* app, int inverse, VkFFTLaunchParams* launch);
 void vkfft_delete(VkFFTApplication* app);
+int vkfft_get_version(void);
+// 1 if glslang is usable: always when linked, after loading it with dlopen-glslang.
+int vkfft_glslang_load(void);
 
 #ifdef __cplusplus
 }